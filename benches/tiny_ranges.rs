@@ -0,0 +1,70 @@
+//! Times `Strloin::from_ranges` on selections made of many tiny, non-abutting ranges, such as
+//! stripping every other character, against a plain loop pushing each slice onto a `String`.
+//! The two are timed in alternating rounds so that noise affects both alike. Run with
+//! `cargo bench --bench tiny_ranges`.
+
+// `std::hint::black_box` is newer than the MSRV, but benchmarks only run on a current toolchain.
+#![allow(clippy::incompatible_msrv)]
+
+use std::hint::black_box;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use strloin::Strloin;
+
+const SOURCE_LEN: usize = 1 << 20;
+const ROUNDS: u32 = 50;
+
+fn push_each(source: &str, ranges: &[Range<usize>]) -> String {
+    let mut owned = String::with_capacity(ranges.iter().map(ExactSizeIterator::len).sum());
+    for range in ranges {
+        owned.push_str(&source[range.clone()]);
+    }
+    owned
+}
+
+fn time<T>(best: &mut Duration, f: impl FnOnce() -> T) {
+    let start = Instant::now();
+    black_box(f());
+    *best = (*best).min(start.elapsed());
+}
+
+fn bench(name: &str, source: &str, ranges: &[Range<usize>]) {
+    let strloin = Strloin::new(source);
+    assert_eq!(strloin.from_ranges(ranges), push_each(source, ranges));
+
+    let mut strloin_best = Duration::MAX;
+    let mut push_best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        time(&mut strloin_best, || strloin.from_ranges(black_box(ranges)));
+        time(&mut push_best, || push_each(source, black_box(ranges)));
+    }
+
+    let per_range = |best: Duration| best.as_secs_f64() * 1e9 / ranges.len() as f64;
+    println!(
+        "{name:<20} from_ranges {:>6.2} ns per range, push_str loop {:>6.2} ns per range",
+        per_range(strloin_best),
+        per_range(push_best),
+    );
+}
+
+fn main() {
+    let source = "abcdefghijklmnopqrstuvwxyz".repeat(SOURCE_LEN / 26 + 1);
+    let source = &source[..SOURCE_LEN];
+
+    for len in 1..=4 {
+        let ranges: Vec<_> = (0..SOURCE_LEN - len)
+            .step_by(len + 1)
+            .map(|start| start..start + len)
+            .collect();
+        bench(&format!("{len} byte ranges"), source, &ranges);
+    }
+
+    let mut ranges = Vec::new();
+    let (mut start, mut len) = (0, 1);
+    while start + len <= SOURCE_LEN {
+        ranges.push(start..start + len);
+        start += len + 1 + len % 2;
+        len = len % 4 + 1;
+    }
+    bench("1-4 byte ranges", source, &ranges);
+}
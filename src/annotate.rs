@@ -0,0 +1,108 @@
+use crate::strloin::Strloin;
+use std::fmt;
+use std::ops::Range;
+
+/// Renders the lines of a source string that ranges select from, with carets under each
+/// selected character, for seeing at a glance what a parser picked out.
+///
+/// Construct one with [`Strloin::annotate`](crate::Strloin::annotate), then format it with
+/// [`Display`](fmt::Display). Lines are numbered from 1, lines with nothing selected are left
+/// out, and empty ranges aren't marked.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::Strloin;
+///
+/// let strloin = Strloin::new("let x = 1;\nlet y = 2;\nlet z = x;");
+/// let annotated = strloin.annotate(&[4..5, 30..31]).to_string();
+/// assert_eq!(
+///     annotated,
+///     "1 | let x = 1;\n  |     ^\n3 | let z = x;\n  |         ^\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Annotated<'a, 'r> {
+    source: &'a str,
+    ranges: &'r [Range<usize>],
+}
+
+impl<'a, 'r> Annotated<'a, 'r> {
+    pub(crate) const fn new(source: &'a str, ranges: &'r [Range<usize>]) -> Self {
+        Self { source, ranges }
+    }
+
+    fn is_selected(&self, index: usize) -> bool {
+        self.ranges.iter().any(|range| range.contains(&index))
+    }
+}
+
+impl fmt::Display for Annotated<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = Strloin::new(self.source).lines_ranges();
+        let width = lines.clone().count().to_string().len();
+        let mut markers = String::new();
+
+        for (number, line) in lines.enumerate() {
+            markers.clear();
+            for (index, _) in self.source[line.clone()].char_indices() {
+                markers.push(if self.is_selected(line.start + index) {
+                    '^'
+                } else {
+                    ' '
+                });
+            }
+
+            let markers = markers.trim_end();
+            if markers.is_empty() {
+                continue;
+            }
+
+            writeln!(
+                f,
+                "{:>width$} | {}",
+                number + 1,
+                &self.source[line],
+                width = width
+            )?;
+            writeln!(f, "{:width$} | {}", "", markers, width = width)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use crate::Strloin;
+
+    #[test]
+    fn annotate() {
+        macro_rules! annotate_ok {
+            ($source:expr, $ranges:expr, $expected:expr) => {
+                let got = Strloin::new($source).annotate(&$ranges).to_string();
+                assert_eq!(got, $expected);
+            };
+        }
+
+        annotate_ok!("", [0..0], "");
+        annotate_ok!("hello world", [], "");
+        annotate_ok!("hello world", [3..3], "");
+        annotate_ok!("hello world", [0..5], "1 | hello world\n  | ^^^^^\n");
+        annotate_ok!(
+            "hello world",
+            [6..11, 0..1, 4..2],
+            "1 | hello world\n  | ^     ^^^^^\n"
+        );
+        annotate_ok!(
+            "ab\r\ncd\n\nef",
+            [1..5, 9..20],
+            "1 | ab\n  |  ^\n2 | cd\n  | ^\n4 | ef\n  |  ^\n"
+        );
+        annotate_ok!("héllo", [1..3, 4..5], "1 | héllo\n  |  ^ ^\n");
+
+        let source = "x\n".repeat(10);
+        annotate_ok!(&source, [18..19], "10 | x\n   | ^\n");
+    }
+}
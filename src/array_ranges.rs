@@ -0,0 +1,234 @@
+use crate::error::Error;
+use crate::ranges::merge_into;
+use std::ops::{Index, Range};
+use std::slice::Iter;
+
+/// A fixed-capacity alternative to [`Ranges`](crate::Ranges) that stores up to `N` ranges
+/// inline, so building a list of ranges never allocates.
+///
+/// Pass [`ArrayRanges::as_slice`] to
+/// [`Strloin::from_ranges`](crate::Strloin::from_ranges) to extract from it.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{ArrayRanges, Borrowed, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+/// let mut ranges = ArrayRanges::<2>::new();
+///
+/// ranges.try_push(0..5).unwrap();
+/// ranges.try_push(5..11).unwrap();
+/// assert!(matches!(strloin.from_ranges(ranges.as_slice()), Borrowed("hello world")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayRanges<const N: usize> {
+    ranges: [Range<usize>; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayRanges<N> {
+    /// Construct a new empty [`ArrayRanges`]. This is a `const fn`, so it can initialize a
+    /// `const` or `static`.
+    #[must_use]
+    pub const fn new() -> Self {
+        const EMPTY: Range<usize> = 0..0;
+        Self {
+            ranges: [EMPTY; N],
+            len: 0,
+        }
+    }
+
+    /// Adds a new range, collapsing it into the last range if possible, just like
+    /// [`Ranges::push`](crate::Ranges::push).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Full`] if the range can't be collapsed and all `N` slots are already in
+    /// use, in which case it is not added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{ArrayRanges, Error};
+    ///
+    /// let mut ranges = ArrayRanges::<1>::new();
+    /// assert_eq!(ranges.try_push(0..5), Ok(()));
+    /// assert_eq!(ranges.try_push(5..8), Ok(()));
+    /// assert_eq!(ranges.try_push(9..11), Err(Error::Full { index: 1, range: 9..11 }));
+    /// assert_eq!(ranges.as_slice(), [0..8]);
+    /// ```
+    pub fn try_push(&mut self, range: Range<usize>) -> Result<(), Error> {
+        if let Some(last) = self.ranges[..self.len].last_mut() {
+            if merge_into(last, &range) {
+                return Ok(());
+            }
+        }
+
+        let Some(slot) = self.ranges.get_mut(self.len) else {
+            return Err(Error::Full {
+                index: self.len,
+                range,
+            });
+        };
+        *slot = range;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the number of ranges, after collapsing.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no ranges.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of ranges, `N`.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the ranges as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[Range<usize>] {
+        &self.ranges[..self.len]
+    }
+
+    /// Returns an iterator over the ranges.
+    pub fn iter(&self) -> Iter<'_, Range<usize>> {
+        self.as_slice().iter()
+    }
+
+    /// Removes the last range and returns it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<Range<usize>> {
+        self.len = self.len.checked_sub(1)?;
+        Some(std::mem::replace(&mut self.ranges[self.len], 0..0))
+    }
+
+    /// Removes all ranges.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for ArrayRanges<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AsRef<[Range<usize>]> for ArrayRanges<N> {
+    fn as_ref(&self) -> &[Range<usize>] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> Index<usize> for ArrayRanges<N> {
+    type Output = Range<usize>;
+
+    /// Returns the range at position `index`, after collapsing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'r, const N: usize> IntoIterator for &'r ArrayRanges<N> {
+    type Item = &'r Range<usize>;
+    type IntoIter = Iter<'r, Range<usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+    use crate::Ranges;
+
+    #[test]
+    fn try_push() {
+        macro_rules! try_push_ok {
+            ($input:expr) => {
+                let input: &[Range<usize>] = &$input;
+                let mut ranges = ArrayRanges::<8>::new();
+                for range in input {
+                    ranges.try_push(range.clone()).expect("try_push");
+                }
+                let expected: Ranges = input.iter().cloned().collect();
+                assert_eq!(ranges.as_slice(), expected.ranges.as_slice());
+                assert_eq!(ranges.len(), expected.len());
+            };
+        }
+
+        try_push_ok!([]);
+        try_push_ok!([0..5]);
+        try_push_ok!([0..5, 5..11]);
+        try_push_ok!([0..5, 6..11]);
+        try_push_ok!([0..0, 0..5, 5..5, 5..8]);
+        try_push_ok!([6..11, 5..6, 0..5, 3..1]);
+    }
+
+    #[test]
+    fn const_new() {
+        const EMPTY: ArrayRanges<4> = ArrayRanges::new();
+        let mut ranges = EMPTY;
+        assert_eq!((ranges.len(), ranges.capacity()), (0, 4));
+        ranges.try_push(0..2).expect("push");
+        assert_eq!(ranges.as_slice(), [0..2]);
+        assert!(EMPTY.is_empty());
+    }
+
+    #[test]
+    fn full() {
+        let mut ranges = ArrayRanges::<2>::new();
+        assert_eq!(ranges.capacity(), 2);
+        ranges.try_push(0..2).expect("first");
+        ranges.try_push(3..5).expect("second");
+        ranges.try_push(5..7).expect("collapsed");
+        assert_eq!(
+            ranges.try_push(0..1),
+            Err(Error::Full {
+                index: 2,
+                range: 0..1
+            })
+        );
+        assert_eq!(ranges.as_slice(), [0..2, 3..7]);
+
+        let mut ranges = ArrayRanges::<0>::new();
+        assert_eq!(
+            ranges.try_push(0..1),
+            Err(Error::Full {
+                index: 0,
+                range: 0..1
+            })
+        );
+        assert!(ranges.is_empty());
+    }
+
+    #[test]
+    fn edit() {
+        let mut ranges = ArrayRanges::<3>::default();
+        ranges.try_push(0..2).expect("first");
+        ranges.try_push(3..5).expect("second");
+        assert_eq!(ranges[1], 3..5);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [&(0..2), &(3..5)]);
+
+        assert_eq!(ranges.pop(), Some(3..5));
+        assert_eq!(ranges.len(), 1);
+        ranges.clear();
+        assert_eq!(ranges.pop(), None);
+        assert!(ranges.is_empty());
+    }
+}
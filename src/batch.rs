@@ -0,0 +1,214 @@
+use crate::cow::{Borrowed, Cow, Owned};
+use std::ops::{Index, Range};
+use std::slice::Iter;
+
+/// The strings extracted by [`Strloin::from_ranges_batch`], one per [`Ranges`].
+///
+/// Results that form a single contiguous region borrow from the source string, and the rest
+/// share one owned buffer instead of allocating a `String` each.
+///
+/// [`Strloin::from_ranges_batch`]: crate::Strloin::from_ranges_batch
+/// [`Ranges`]: crate::Ranges
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Ranges, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+/// let fields: Vec<Ranges> = vec![
+///     Ranges::from(0..5),
+///     [0..1, 6..7].into_iter().collect(),
+///     [6..11, 5..6, 0..5].into_iter().collect(),
+/// ];
+///
+/// let batch = strloin.from_ranges_batch(&fields);
+/// assert_eq!(batch.iter().collect::<Vec<_>>(), ["hello", "hw", "world hello"]);
+/// assert!(batch.is_borrowed(0));
+/// assert_eq!(&batch[1], "hw");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Batch<'a> {
+    buffer: String,
+    entries: Vec<Entry<'a>>,
+}
+
+/// Where the string for one result of a [`Batch`] lives: borrowed from the source string, or
+/// at a range of the shared buffer.
+#[derive(Debug, Clone)]
+enum Entry<'a> {
+    Borrowed(&'a str),
+    Owned(Range<usize>),
+}
+
+impl<'a> Batch<'a> {
+    pub(crate) fn with_capacity(capacity: usize, len: usize) -> Self {
+        Self {
+            buffer: String::with_capacity(capacity),
+            entries: Vec::with_capacity(len),
+        }
+    }
+
+    /// Adds a result that borrows from the source string.
+    pub(crate) fn push_borrowed(&mut self, borrowed: &'a str) {
+        self.entries.push(Entry::Borrowed(borrowed));
+    }
+
+    /// Adds a result concatenating the given segments into the shared buffer.
+    pub(crate) fn push_owned<'s, T: Iterator<Item = &'s str>>(&mut self, segments: T) {
+        let start = self.buffer.len();
+        for segment in segments {
+            self.buffer.push_str(segment);
+        }
+        self.entries.push(Entry::Owned(start..self.buffer.len()));
+    }
+
+    /// Returns the number of results.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no results.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the result at `index`, or `None` if it is out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| self.resolve(entry))
+    }
+
+    /// Returns `true` if the result at `index` borrows from the source string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn is_borrowed(&self, index: usize) -> bool {
+        matches!(self.entries[index], Entry::Borrowed(_))
+    }
+
+    /// Returns the result at `index` as a [`Cow`], which is borrowed from the source string if
+    /// possible, or else copied out of the shared buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn to_cow(&self, index: usize) -> Cow<'a, str> {
+        match &self.entries[index] {
+            Entry::Borrowed(borrowed) => Borrowed(borrowed),
+            Entry::Owned(range) => Owned(self.buffer[range.clone()].to_owned()),
+        }
+    }
+
+    /// Returns an iterator over the results, in order.
+    #[must_use]
+    pub fn iter(&self) -> BatchIter<'_, 'a> {
+        BatchIter {
+            batch: self,
+            entries: self.entries.iter(),
+        }
+    }
+
+    fn resolve<'b>(&'b self, entry: &Entry<'a>) -> &'b str {
+        match entry {
+            Entry::Borrowed(borrowed) => borrowed,
+            Entry::Owned(range) => &self.buffer[range.clone()],
+        }
+    }
+}
+
+impl Index<usize> for Batch<'_> {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.resolve(&self.entries[index])
+    }
+}
+
+impl<'b> IntoIterator for &'b Batch<'_> {
+    type Item = &'b str;
+    type IntoIter = BatchIter<'b, 'b>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the results of a [`Batch`], created by [`Batch::iter`].
+#[derive(Debug, Clone)]
+pub struct BatchIter<'b, 'a> {
+    batch: &'b Batch<'a>,
+    entries: Iter<'b, Entry<'a>>,
+}
+
+impl<'b> Iterator for BatchIter<'b, '_> {
+    type Item = &'b str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.batch;
+        self.entries.next().map(|entry| batch.resolve(entry))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for BatchIter<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let batch = self.batch;
+        self.entries.next_back().map(|entry| batch.resolve(entry))
+    }
+}
+
+impl ExactSizeIterator for BatchIter<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Borrowed, Owned, Ranges, Strloin};
+
+    #[test]
+    fn from_ranges_batch() {
+        let strloin = Strloin::new("hello world");
+        let fields: Vec<Ranges> = vec![
+            Ranges::new(),
+            Ranges::from(6..11),
+            [0..5, 6..11].into_iter().collect(),
+            [0..5, 5..11].into_iter().collect(),
+            [10..11, 4..5].into_iter().collect(),
+        ];
+
+        let batch = strloin.from_ranges_batch(&fields);
+        assert_eq!(batch.len(), fields.len());
+        for (index, ranges) in fields.iter().enumerate() {
+            let expected = strloin.from_ranges_obj(ranges);
+            assert_eq!(&batch[index], &*expected);
+            assert_eq!(batch.get(index), Some(&*expected));
+            assert_eq!(batch.to_cow(index), expected);
+            assert_eq!(
+                batch.is_borrowed(index),
+                matches!(expected, Borrowed(_)),
+                "borrow"
+            );
+            assert_eq!(
+                matches!(batch.to_cow(index), Owned(_)),
+                matches!(expected, Owned(_))
+            );
+        }
+        assert_eq!(batch.get(fields.len()), None);
+
+        assert_eq!(
+            batch.iter().rev().collect::<Vec<_>>(),
+            ["do", "hello world", "helloworld", "world", ""]
+        );
+        assert_eq!((&batch).into_iter().len(), 5);
+
+        let empty = strloin.from_ranges_batch(&[]);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::cow::Cow;
+use crate::ranges::{Checkpoint, Ranges};
+use crate::selection::Selection;
+use crate::strloin::Strloin;
+use std::ops::Range;
+
+/// Builds up the [`Ranges`] selected from a source string, keeping the two together so that
+/// the ranges can't be extracted from the wrong source.
+///
+/// Construct one with [`Strloin::builder`](crate::Strloin::builder).
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+///
+/// let mut builder = strloin.builder();
+/// builder.push(0..5).push_len(5, 6);
+/// assert!(matches!(builder.build(), Borrowed("hello world")));
+///
+/// let checkpoint = builder.checkpoint();
+/// builder.push(0..5);
+/// assert_eq!(builder.build(), "hello worldhello");
+///
+/// builder.rollback(checkpoint);
+/// assert_eq!(builder.build(), "hello world");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SelectionBuilder<'a> {
+    source: &'a str,
+    ranges: Ranges,
+}
+
+impl<'a> SelectionBuilder<'a> {
+    pub(crate) const fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            ranges: Ranges::new(),
+        }
+    }
+
+    /// Adds a new range, collapsing if possible, as with [`Ranges::push`].
+    pub fn push(&mut self, range: Range<usize>) -> &mut Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Adds a new range of `len` bytes starting at `start`, collapsing if possible, as with
+    /// [`Ranges::push_len`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start + len` overflows `usize`.
+    pub fn push_len(&mut self, start: usize, len: usize) -> &mut Self {
+        self.ranges.push_len(start, len);
+        self
+    }
+
+    /// Records the current state of the ranges, as with [`Ranges::checkpoint`].
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.ranges.checkpoint()
+    }
+
+    /// Undoes every push made since the checkpoint, as with [`Ranges::rollback`].
+    pub fn rollback(&mut self, checkpoint: Checkpoint) -> &mut Self {
+        self.ranges.rollback(checkpoint);
+        self
+    }
+
+    /// Returns the ranges built so far.
+    #[must_use]
+    pub const fn ranges(&self) -> &Ranges {
+        &self.ranges
+    }
+
+    /// Extracts the string built so far, borrowing from the source string if possible, as with
+    /// [`Strloin::from_ranges_obj`].
+    #[must_use]
+    pub fn build(&self) -> Cow<'a, str> {
+        Strloin::new(self.source).from_ranges_obj(&self.ranges)
+    }
+
+    /// Converts the builder into a [`Selection`] of its ranges.
+    #[must_use]
+    pub fn into_selection(self) -> Selection<'a> {
+        Selection::new(self.source, self.ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Borrowed, Strloin};
+
+    #[test]
+    fn build() {
+        let strloin = Strloin::new("hello world");
+        let mut builder = strloin.builder();
+        assert!(matches!(builder.build(), Borrowed("")));
+
+        builder.push(6..11).push(5..6);
+        assert_eq!(builder.ranges().ranges, [6..11, 5..6]);
+        assert_eq!(builder.build(), "world ");
+
+        let checkpoint = builder.checkpoint();
+        builder.push_len(6, 0).push_len(0, 5);
+        assert_eq!(builder.build(), "world hello");
+        builder.rollback(checkpoint).push(0..1);
+        assert_eq!(builder.build(), "world h");
+
+        let selection = builder.into_selection();
+        assert_eq!(selection, "world h");
+    }
+}
@@ -1 +1,161 @@
 pub use std::borrow::Cow::{self, Borrowed, Owned};
+
+/// String operations on a [`Cow`] that keep a borrowed string borrowed whenever the operation
+/// doesn't change it, so post-processing the result of [`Strloin::from_ranges`] doesn't throw away
+/// the borrow.
+///
+/// [`Strloin::from_ranges`]: crate::Strloin::from_ranges
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, CowStrExt, Owned, Strloin};
+///
+/// let strloin = Strloin::new(" hello world ");
+///
+/// let cow = strloin.from_ranges(&[0..6]).cow_trim().cow_to_lowercase();
+/// assert!(matches!(cow, Borrowed("hello")));
+///
+/// let cow = strloin.from_ranges(&[0..6]).cow_trim().cow_replace("l", "L");
+/// assert!(matches!(cow, Owned(_)));
+/// assert_eq!(cow, "heLLo");
+/// ```
+pub trait CowStrExt<'a> {
+    /// Replaces all matches of a pattern with another string, as with [`str::replace`].
+    #[must_use]
+    fn cow_replace(self, from: &str, to: &str) -> Cow<'a, str>;
+
+    /// Returns the lowercase equivalent of the string, as with [`str::to_lowercase`].
+    #[must_use]
+    fn cow_to_lowercase(self) -> Cow<'a, str>;
+
+    /// Returns the uppercase equivalent of the string, as with [`str::to_uppercase`].
+    #[must_use]
+    fn cow_to_uppercase(self) -> Cow<'a, str>;
+
+    /// Removes leading and trailing whitespace, as with [`str::trim`].
+    #[must_use]
+    fn cow_trim(self) -> Cow<'a, str>;
+
+    /// Removes leading whitespace, as with [`str::trim_start`].
+    #[must_use]
+    fn cow_trim_start(self) -> Cow<'a, str>;
+
+    /// Removes trailing whitespace, as with [`str::trim_end`].
+    #[must_use]
+    fn cow_trim_end(self) -> Cow<'a, str>;
+}
+
+impl<'a> CowStrExt<'a> for Cow<'a, str> {
+    fn cow_replace(self, from: &str, to: &str) -> Self {
+        if self.contains(from) {
+            Owned(self.replace(from, to))
+        } else {
+            self
+        }
+    }
+
+    fn cow_to_lowercase(self) -> Self {
+        if self.chars().all(|c| maps_to_itself(c.to_lowercase(), c)) {
+            self
+        } else {
+            Owned(self.to_lowercase())
+        }
+    }
+
+    fn cow_to_uppercase(self) -> Self {
+        if self.chars().all(|c| maps_to_itself(c.to_uppercase(), c)) {
+            self
+        } else {
+            Owned(self.to_uppercase())
+        }
+    }
+
+    fn cow_trim(self) -> Self {
+        self.cow_trim_end().cow_trim_start()
+    }
+
+    fn cow_trim_start(self) -> Self {
+        match self {
+            Borrowed(s) => Borrowed(s.trim_start()),
+            Owned(mut s) => {
+                let len = s.len() - s.trim_start().len();
+                s.drain(..len);
+                Owned(s)
+            }
+        }
+    }
+
+    fn cow_trim_end(self) -> Self {
+        match self {
+            Borrowed(s) => Borrowed(s.trim_end()),
+            Owned(mut s) => {
+                let len = s.trim_end().len();
+                s.truncate(len);
+                Owned(s)
+            }
+        }
+    }
+}
+
+/// Returns `true` if a case mapping of `c` is just `c` itself.
+fn maps_to_itself<I: Iterator<Item = char>>(mut mapping: I, c: char) -> bool {
+    mapping.next() == Some(c) && mapping.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! cow_ok {
+        ($method:ident($($arg:expr),*), $input:expr, $expected:expr, $is_borrow:expr) => {
+            let input: &str = $input;
+            let expected: &str = $expected;
+
+            let got = Borrowed(input).$method($($arg),*);
+            assert_eq!(got, expected, "{} from borrowed", stringify!($method));
+            if $is_borrow {
+                assert!(matches!(got, Borrowed(_)), "expected borrow");
+            } else {
+                assert!(matches!(got, Owned(_)), "expected owned");
+            }
+
+            let got = Cow::<str>::Owned(input.to_owned()).$method($($arg),*);
+            assert_eq!(got, expected, "{} from owned", stringify!($method));
+            assert!(matches!(got, Owned(_)), "expected owned");
+        };
+    }
+
+    #[test]
+    fn replace() {
+        cow_ok!(cow_replace("l", "L"), "", "", true);
+        cow_ok!(cow_replace("l", "L"), "hello", "heLLo", false);
+        cow_ok!(cow_replace("x", "L"), "hello", "hello", true);
+        cow_ok!(cow_replace("", "-"), "hi", "-h-i-", false);
+    }
+
+    #[test]
+    fn case() {
+        cow_ok!(cow_to_lowercase(), "", "", true);
+        cow_ok!(cow_to_lowercase(), "hello world", "hello world", true);
+        cow_ok!(cow_to_lowercase(), "Hello World", "hello world", false);
+        cow_ok!(cow_to_lowercase(), "straße", "straße", true);
+        cow_ok!(cow_to_lowercase(), "ΟΔΟΣ", "οδος", false);
+        cow_ok!(cow_to_lowercase(), "ǅ", "ǆ", false);
+
+        cow_ok!(cow_to_uppercase(), "", "", true);
+        cow_ok!(cow_to_uppercase(), "HELLO 123", "HELLO 123", true);
+        cow_ok!(cow_to_uppercase(), "Hello", "HELLO", false);
+        cow_ok!(cow_to_uppercase(), "straße", "STRASSE", false);
+    }
+
+    #[test]
+    fn trim() {
+        cow_ok!(cow_trim(), "", "", true);
+        cow_ok!(cow_trim(), "hello", "hello", true);
+        cow_ok!(cow_trim(), " \thello\n ", "hello", true);
+        cow_ok!(cow_trim(), "   ", "", true);
+        cow_ok!(cow_trim_start(), " \thello\n ", "hello\n ", true);
+        cow_ok!(cow_trim_end(), " \thello\n ", " \thello", true);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::cow::Cow;
+use crate::ranges::{Checkpoint, Ranges};
+use crate::strloin::Strloin;
+
+/// A scanner over a source string that records the ranges it takes, so that everything taken
+/// can be extracted at the end, borrowing if it forms a single contiguous region.
+///
+/// Taking operations ([`Cursor::take`], [`Cursor::take_while`], and [`Cursor::expect`]) advance
+/// the cursor and push what they consumed onto its [`Ranges`], while skipping operations
+/// ([`Cursor::skip`] and [`Cursor::skip_while`]) only advance it. Positions always stay on
+/// character boundaries.
+///
+/// Construct one with [`Strloin::cursor`](crate::Strloin::cursor).
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, Strloin};
+///
+/// let strloin = Strloin::new("  key = \"some value\"");
+/// let mut cursor = strloin.cursor();
+///
+/// cursor.skip_while(char::is_whitespace);
+/// assert_eq!(cursor.take_while(char::is_alphanumeric), "key");
+/// assert!(matches!(cursor.emit(), Borrowed("key")));
+///
+/// cursor.skip_while(|c| c == ' ' || c == '=' || c == '"');
+/// cursor.take_while(|c| c != '"');
+/// assert_eq!(cursor.emit(), "keysome value");
+/// assert_eq!(cursor.rest(), "\"");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    source: &'a str,
+    position: usize,
+    ranges: Ranges,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) const fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+            ranges: Ranges::new(),
+        }
+    }
+
+    /// Returns the byte offset of the cursor in the source string.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the part of the source string that hasn't been consumed yet.
+    #[must_use]
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    /// Returns `true` if the whole source string has been consumed.
+    #[must_use]
+    pub const fn is_done(&self) -> bool {
+        self.position == self.source.len()
+    }
+
+    /// Returns the next character without consuming it, or `None` if the cursor is done.
+    #[must_use]
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Consumes and records up to `n` characters, returning them.
+    pub fn take(&mut self, n: usize) -> &'a str {
+        let len = self.len_of(n);
+        self.advance(len, true)
+    }
+
+    /// Consumes and records characters for as long as `predicate` returns `true`, returning them.
+    pub fn take_while<P: FnMut(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let len = self.len_while(predicate);
+        self.advance(len, true)
+    }
+
+    /// Consumes up to `n` characters without recording them, returning them.
+    pub fn skip(&mut self, n: usize) -> &'a str {
+        let len = self.len_of(n);
+        self.advance(len, false)
+    }
+
+    /// Consumes characters for as long as `predicate` returns `true` without recording them,
+    /// returning them.
+    pub fn skip_while<P: FnMut(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let len = self.len_while(predicate);
+        self.advance(len, false)
+    }
+
+    /// Consumes and records `expected` if the rest of the source string starts with it,
+    /// returning the consumed slice. Otherwise, returns `None` and leaves the cursor unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let mut cursor = Strloin::new("fn main").cursor();
+    /// assert_eq!(cursor.expect("let"), None);
+    /// assert_eq!(cursor.expect("fn"), Some("fn"));
+    /// assert_eq!(cursor.rest(), " main");
+    /// ```
+    pub fn expect(&mut self, expected: &str) -> Option<&'a str> {
+        self.rest()
+            .starts_with(expected)
+            .then(|| self.advance(expected.len(), true))
+    }
+
+    /// Records the position of the cursor and the ranges taken so far, so that
+    /// [`Cursor::rollback`] can backtrack to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let mut cursor = Strloin::new("abc123").cursor();
+    /// cursor.take(1);
+    ///
+    /// let checkpoint = cursor.checkpoint();
+    /// cursor.take_while(char::is_alphabetic);
+    /// if cursor.expect("!").is_none() {
+    ///     cursor.rollback(checkpoint);
+    /// }
+    /// assert_eq!((cursor.rest(), cursor.emit().as_ref()), ("bc123", "a"));
+    /// ```
+    #[must_use]
+    pub fn checkpoint(&self) -> CursorCheckpoint {
+        CursorCheckpoint {
+            position: self.position,
+            ranges: self.ranges.checkpoint(),
+        }
+    }
+
+    /// Restores the cursor to the state recorded by [`Cursor::checkpoint`], moving it back to
+    /// that position and undoing everything taken since, including takes that were collapsed
+    /// into an existing range.
+    pub fn rollback(&mut self, checkpoint: CursorCheckpoint) {
+        self.position = checkpoint.position;
+        self.ranges.rollback(checkpoint.ranges);
+    }
+
+    /// Returns the ranges taken so far.
+    #[must_use]
+    pub const fn ranges(&self) -> &Ranges {
+        &self.ranges
+    }
+
+    /// Converts the cursor into the ranges it took.
+    #[must_use]
+    pub fn into_ranges(self) -> Ranges {
+        self.ranges
+    }
+
+    /// Extracts everything taken so far, borrowing from the source string if possible, as with
+    /// [`Strloin::from_ranges_obj`].
+    #[must_use]
+    pub fn emit(&self) -> Cow<'a, str> {
+        Strloin::new(self.source).from_ranges_obj(&self.ranges)
+    }
+
+    fn len_of(&self, n: usize) -> usize {
+        let rest = self.rest();
+        rest.char_indices().nth(n).map_or(rest.len(), |(i, _)| i)
+    }
+
+    fn len_while<P: FnMut(char) -> bool>(&self, mut predicate: P) -> usize {
+        let rest = self.rest();
+        rest.char_indices()
+            .find(|&(_, c)| !predicate(c))
+            .map_or(rest.len(), |(i, _)| i)
+    }
+
+    fn advance(&mut self, len: usize, record: bool) -> &'a str {
+        let range = self.position..self.position + len;
+        self.position = range.end;
+        if record {
+            self.ranges.push(range.clone());
+        }
+        &self.source[range]
+    }
+}
+
+/// The state of a [`Cursor`] recorded by [`Cursor::checkpoint`], for backtracking with
+/// [`Cursor::rollback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorCheckpoint {
+    position: usize,
+    ranges: Checkpoint,
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use crate::{Borrowed, Strloin};
+
+    #[test]
+    fn take() {
+        let strloin = Strloin::new("héllo, wörld");
+        let mut cursor = strloin.cursor();
+        assert!(matches!(cursor.emit(), Borrowed("")));
+        assert_eq!(cursor.peek(), Some('h'));
+
+        assert_eq!(cursor.take(2), "hé");
+        assert_eq!(cursor.take(0), "");
+        assert_eq!(cursor.take_while(|c| c != ','), "llo");
+        assert_eq!(cursor.position(), 6);
+        assert!(matches!(cursor.emit(), Borrowed("héllo")));
+
+        assert_eq!(cursor.skip(2), ", ");
+        assert_eq!(cursor.take(100), "wörld");
+        assert!(cursor.is_done());
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.take(1), "");
+        assert_eq!(cursor.take_while(|_| true), "");
+        assert_eq!(cursor.ranges().ranges, [0..6, 8..14]);
+        assert_eq!(cursor.emit(), "héllowörld");
+        assert_eq!(cursor.into_ranges().ranges, [0..6, 8..14]);
+    }
+
+    #[test]
+    fn rollback() {
+        let strloin = Strloin::new("ab cd ef");
+        let mut cursor = strloin.cursor();
+        let start = cursor.checkpoint();
+
+        cursor.take(2);
+        let first = cursor.checkpoint();
+        cursor.take(1);
+        cursor.take(2);
+        assert_eq!(cursor.ranges().ranges, [0..5]);
+
+        cursor.rollback(first);
+        assert_eq!(
+            (cursor.position(), cursor.ranges().ranges.clone()),
+            (2, vec![0..2])
+        );
+        cursor.skip(1);
+        let second = cursor.checkpoint();
+        cursor.take(2);
+        cursor.skip(1);
+        cursor.take(2);
+        assert_eq!(cursor.emit(), "abcdef");
+
+        cursor.rollback(second);
+        assert_eq!((cursor.rest(), cursor.emit().as_ref()), ("cd ef", "ab"));
+        cursor.rollback(start);
+        assert!(cursor.ranges().is_empty());
+        assert_eq!(cursor.rest(), "ab cd ef");
+    }
+
+    #[test]
+    fn skip() {
+        let mut cursor = Strloin::new("  a  b").cursor();
+        assert_eq!(cursor.skip_while(char::is_whitespace), "  ");
+        assert_eq!(cursor.skip_while(char::is_whitespace), "");
+        assert_eq!(cursor.expect("b"), None);
+        assert_eq!(cursor.expect("a"), Some("a"));
+        assert_eq!(cursor.skip(1), " ");
+        assert_eq!(cursor.expect(""), Some(""));
+        assert_eq!(cursor.take(1), " ");
+        assert_eq!(cursor.rest(), "b");
+        assert_eq!(cursor.emit(), "a ");
+        assert_eq!(cursor.ranges().ranges, [2..3, 4..5]);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::template::Template;
+use std::ops::Range;
+
+/// Builds a [`Template`] that reproduces `target` from ranges of `source`, with literals for
+/// whatever can't be found in `source`.
+///
+/// This is greedy: at each position of `target`, it takes the longest piece of `source` that
+/// matches, preferring to continue the previous piece so that neighbouring ranges collapse.
+/// Finding each piece scans all of `source`, so this is meant for retrofitting, not hot loops.
+pub fn diff<'a, 't>(source: &'a str, target: &'t str) -> Template<'a, 't> {
+    let mut template = Template::new(source);
+    let mut literal = 0;
+    let mut position = 0;
+    let mut prefer = 0;
+
+    while position < target.len() {
+        let rest = &target[position..];
+        if let Some(range) = longest_match(source, rest, prefer) {
+            template = template.literal(&target[literal..position]);
+            position += range.len();
+            literal = position;
+            prefer = range.end;
+            template = template.range(range);
+        } else {
+            position += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    template.literal(&target[literal..])
+}
+
+/// Returns the longest substring common to `source` and `candidate`, as its range in each, or
+/// `None` if they have no character in common. Ties go to the earliest match in `candidate`,
+/// then in `source`.
+pub fn longest_common_substring(
+    source: &str,
+    candidate: &str,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let mut best: Option<(Range<usize>, Range<usize>)> = None;
+    for (start, _) in candidate.char_indices() {
+        if best
+            .as_ref()
+            .map_or(false, |(range, _)| range.len() >= candidate.len() - start)
+        {
+            break;
+        }
+
+        if let Some(range) = longest_match(source, &candidate[start..], 0) {
+            if best
+                .as_ref()
+                .map_or(true, |(best, _)| range.len() > best.len())
+            {
+                best = Some((range.clone(), start..start + range.len()));
+            }
+        }
+    }
+
+    best
+}
+
+/// Returns the range of the longest prefix of `rest` that appears in `source`, choosing the one
+/// starting at `prefer` if there's a tie, and otherwise the earliest, or `None` if not even one
+/// character matches.
+pub fn longest_match(source: &str, rest: &str, prefer: usize) -> Option<Range<usize>> {
+    let common = |start: usize| {
+        let len = source.as_bytes()[start..]
+            .iter()
+            .zip(rest.as_bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        // Both sides agree byte-for-byte, so backing off to a character boundary of `rest`
+        // also lands on one in `source`.
+        (0..=len).rev().find(|&len| rest.is_char_boundary(len))
+    };
+
+    let mut best = prefer..prefer + common(prefer).unwrap_or(0);
+    for (start, _) in source.char_indices() {
+        let len = common(start).unwrap_or(0);
+        if len > best.len() {
+            best = start..start + len;
+        }
+    }
+
+    (!best.is_empty()).then_some(best)
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use crate::{Borrowed, Owned, Strloin};
+
+    #[test]
+    fn diff() {
+        macro_rules! diff_ok {
+            ($source:expr, $target:expr, $ranges:expr, $borrowed:expr) => {
+                let target: &str = $target;
+                let template = Strloin::new($source).diff(target);
+                let ranges: Vec<_> = template.ranges().cloned().collect();
+                assert_eq!(ranges, $ranges, "ranges");
+
+                let got = template.build();
+                assert_eq!(got, target);
+                assert_eq!(matches!(got, Borrowed(_)), $borrowed, "borrowed");
+            };
+        }
+
+        diff_ok!("hello world", "", [], true);
+        diff_ok!("", "new", [], false);
+        diff_ok!("hello world", "hello world", [0..11], true);
+        diff_ok!("hello world", "lo wo", [3..8], true);
+        diff_ok!("hello world", "world hello", [6..11, 5..6, 0..5], false);
+        diff_ok!("hello world", "hello, world!", [0..5, 5..11], false);
+        diff_ok!("abcabc", "abcab", [0..5], true);
+        diff_ok!("xyz", "qqq", [], false);
+        diff_ok!("héllo", "hèllo", [0..1, 3..6], false);
+        diff_ok!("naïve café", "café naïve", [7..12, 6..7, 0..6], false);
+    }
+
+    #[test]
+    fn longest_prefix_match() {
+        let strloin = Strloin::new("hello world");
+        assert_eq!(strloin.longest_prefix_match("world peace"), Some(6..11));
+        assert_eq!(strloin.longest_prefix_match("lo"), Some(3..5));
+        assert_eq!(strloin.longest_prefix_match("l"), Some(2..3));
+        assert_eq!(strloin.longest_prefix_match("xhello"), None);
+        assert_eq!(strloin.longest_prefix_match(""), None);
+        assert_eq!(Strloin::new("é").longest_prefix_match("è"), None);
+    }
+
+    #[test]
+    fn longest_common_substring() {
+        let strloin = Strloin::new("hello world");
+        assert_eq!(
+            strloin.longest_common_substring("say hello!"),
+            Some((0..5, 4..9))
+        );
+        assert_eq!(
+            strloin.longest_common_substring("a world"),
+            Some((5..11, 1..7))
+        );
+        assert_eq!(strloin.longest_common_substring("lol"), Some((3..5, 0..2)));
+        assert_eq!(strloin.longest_common_substring("xyz"), None);
+        assert_eq!(strloin.longest_common_substring(""), None);
+        assert_eq!(Strloin::new("").longest_common_substring("abc"), None);
+        assert_eq!(
+            Strloin::new("naïve").longest_common_substring("ï"),
+            Some((2..4, 0..2))
+        );
+    }
+
+    #[test]
+    fn prefers_continuation() {
+        let template = Strloin::new("ab ab").diff("abab");
+        assert_eq!(template.ranges().cloned().collect::<Vec<_>>(), [0..2, 0..2]);
+
+        let template = Strloin::new("a a b").diff("a b");
+        assert_eq!(template.ranges().cloned().collect::<Vec<_>>(), [2..5]);
+        assert!(matches!(template.build(), Borrowed("a b")));
+
+        let got = Strloin::new("ab").diff("a-b").build();
+        assert!(matches!(got, Owned(ref s) if s == "a-b"));
+    }
+}
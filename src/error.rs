@@ -0,0 +1,216 @@
+use std::fmt;
+use std::ops::Range;
+
+/// The error type for fallible operations on ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The range at `index` would overflow (or underflow) `usize`.
+    Overflow { index: usize, range: Range<usize> },
+
+    /// The range at `index`, given as a start and length, would end past `usize::MAX`.
+    LengthOverflow {
+        index: usize,
+        start: usize,
+        len: usize,
+    },
+
+    /// The range at `index` starts after it ends.
+    Inverted { index: usize, range: Range<usize> },
+
+    /// The range at `index` ends past the end of the source string, which is `len` bytes long.
+    OutOfBounds {
+        index: usize,
+        range: Range<usize>,
+        len: usize,
+    },
+
+    /// The range at `index` starts or ends at byte `pos`, which is inside a character.
+    NotCharBoundary { index: usize, pos: usize },
+
+    /// The range at `index` starts before the end of the range preceding it.
+    OutOfOrder {
+        index: usize,
+        range: Range<usize>,
+        previous: Range<usize>,
+    },
+
+    /// The text of the range at `index` isn't of the form `start..end`.
+    Syntax { index: usize, text: String },
+
+    /// The range would be stored at `index`, but that's past the fixed capacity.
+    Full { index: usize, range: Range<usize> },
+
+    /// The encoded ranges are truncated or malformed at byte `offset`.
+    Decode { offset: usize },
+
+    /// The `field` (`"start"` or `"len"`) of the range at `index` is `value`, which doesn't fit
+    /// in `usize`.
+    OffsetOverflow {
+        index: usize,
+        field: &'static str,
+        value: String,
+    },
+
+    /// The result needs `needed` bytes, but the buffer provided for it is only `len` bytes long.
+    BufferTooSmall { needed: usize, len: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow { index, range } => {
+                write!(f, "range {range:?} at index {index} overflows")
+            }
+            Self::LengthOverflow { index, start, len } => write!(
+                f,
+                "range at index {index} starting at {start} with length {len} overflows"
+            ),
+            Self::Inverted { index, range } => {
+                write!(
+                    f,
+                    "range {range:?} at index {index} starts after it ends"
+                )
+            }
+            Self::OutOfBounds { index, range, len } => write!(
+                f,
+                "range {range:?} at index {index} is out of bounds of a string of length {len}"
+            ),
+            Self::NotCharBoundary { index, pos } => write!(
+                f,
+                "range at index {index} splits a character at byte {pos}"
+            ),
+            Self::OutOfOrder {
+                index,
+                range,
+                previous,
+            } => write!(
+                f,
+                "range {range:?} at index {index} starts before the end of the preceding range {previous:?}"
+            ),
+            Self::Syntax { index, text } => write!(
+                f,
+                "invalid range {text:?} at index {index}, expected `start..end`"
+            ),
+            Self::Full { index, range } => write!(
+                f,
+                "range {range:?} at index {index} exceeds the capacity"
+            ),
+            Self::Decode { offset } => write!(f, "invalid encoded ranges at byte {offset}"),
+            Self::OffsetOverflow {
+                index,
+                field,
+                value,
+            } => write!(
+                f,
+                "range at index {index} has a {field} of {value}, which doesn't fit in usize"
+            ),
+            Self::BufferTooSmall { needed, len } => write!(
+                f,
+                "result of {needed} bytes doesn't fit in a buffer of length {len}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+#[allow(clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let error = Error::Overflow {
+            index: 1,
+            range: 3..5,
+        };
+        assert_eq!(error.to_string(), "range 3..5 at index 1 overflows");
+
+        let error = Error::LengthOverflow {
+            index: 3,
+            start: 10,
+            len: usize::MAX,
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "range at index 3 starting at 10 with length {} overflows",
+                usize::MAX
+            )
+        );
+
+        let error = Error::Inverted {
+            index: 0,
+            range: 5..3,
+        };
+        assert_eq!(
+            error.to_string(),
+            "range 5..3 at index 0 starts after it ends"
+        );
+
+        let error = Error::OutOfBounds {
+            index: 1,
+            range: 3..12,
+            len: 11,
+        };
+        assert_eq!(
+            error.to_string(),
+            "range 3..12 at index 1 is out of bounds of a string of length 11"
+        );
+
+        let error = Error::NotCharBoundary { index: 2, pos: 5 };
+        assert_eq!(
+            error.to_string(),
+            "range at index 2 splits a character at byte 5"
+        );
+
+        let error = Error::OutOfOrder {
+            index: 2,
+            range: 4..6,
+            previous: 3..5,
+        };
+        assert_eq!(
+            error.to_string(),
+            "range 4..6 at index 2 starts before the end of the preceding range 3..5"
+        );
+
+        let error = Error::Syntax {
+            index: 1,
+            text: "4-6".to_owned(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid range \"4-6\" at index 1, expected `start..end`"
+        );
+
+        let error = Error::Full {
+            index: 4,
+            range: 8..9,
+        };
+        assert_eq!(
+            error.to_string(),
+            "range 8..9 at index 4 exceeds the capacity"
+        );
+
+        let error = Error::Decode { offset: 7 };
+        assert_eq!(error.to_string(), "invalid encoded ranges at byte 7");
+
+        let error = Error::OffsetOverflow {
+            index: 4,
+            field: "len",
+            value: "-1".to_owned(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "range at index 4 has a len of -1, which doesn't fit in usize"
+        );
+
+        let error = Error::BufferTooSmall { needed: 10, len: 4 };
+        assert_eq!(
+            error.to_string(),
+            "result of 10 bytes doesn't fit in a buffer of length 4"
+        );
+    }
+}
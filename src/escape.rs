@@ -0,0 +1,185 @@
+use crate::cow::{Borrowed, Cow, Owned};
+
+/// A table of escape sequences, as pairs of a raw string and its escaped form, for use with
+/// [`escape`] and [`unescape`]. Earlier pairs take precedence when more than one matches.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{escape, Escapes};
+///
+/// const SHELL: Escapes = Escapes::new(&[("'", "'\\''")]);
+///
+/// assert_eq!(escape("it's", &SHELL), "it'\\''s");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Escapes<'e> {
+    pub pairs: &'e [(&'e str, &'e str)],
+}
+
+impl<'e> Escapes<'e> {
+    /// Backslash escapes for backslashes, double quotes, and common control characters.
+    pub const BACKSLASH: Escapes<'static> = Escapes::new(&[
+        ("\\", "\\\\"),
+        ("\"", "\\\""),
+        ("\n", "\\n"),
+        ("\r", "\\r"),
+        ("\t", "\\t"),
+        ("\0", "\\0"),
+    ]);
+
+    /// HTML entities for the characters that are significant in markup and attributes.
+    pub const HTML: Escapes<'static> = Escapes::new(&[
+        ("&", "&amp;"),
+        ("<", "&lt;"),
+        (">", "&gt;"),
+        ("\"", "&quot;"),
+        ("'", "&#39;"),
+    ]);
+
+    /// Construct a new [`Escapes`] from pairs of a raw string and its escaped form.
+    #[must_use]
+    pub const fn new(pairs: &'e [(&'e str, &'e str)]) -> Self {
+        Escapes { pairs }
+    }
+}
+
+/// Escapes every raw string in the input; if there is nothing to escape, then the result will
+/// borrow from the input.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{escape, Borrowed, Escapes};
+///
+/// assert_eq!(escape("a \"quote\"", &Escapes::BACKSLASH), "a \\\"quote\\\"");
+/// assert!(matches!(escape("no quote", &Escapes::BACKSLASH), Borrowed("no quote")));
+/// ```
+#[must_use]
+pub fn escape<'a>(input: &'a str, escapes: &Escapes<'_>) -> Cow<'a, str> {
+    translate(input, escapes, false)
+}
+
+/// Unescapes every escape sequence in the input; if there are no escape sequences, then the
+/// result will borrow from the input.
+///
+/// This pairs well with [`Strloin::map_ranges`] for extracting
+/// quoted strings that rarely contain escapes.
+///
+/// [`Strloin::map_ranges`]: crate::Strloin::map_ranges
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{unescape, Borrowed, Escapes, Owned, Strloin};
+///
+/// let strloin = Strloin::new(r#"say "hi" and "a \"quote\"""#);
+///
+/// let got = strloin.map_ranges(&[5..7], |s| unescape(s, &Escapes::BACKSLASH));
+/// assert!(matches!(got, Borrowed("hi")));
+///
+/// let got = strloin.map_ranges(&[14..25], |s| unescape(s, &Escapes::BACKSLASH));
+/// assert!(matches!(got, Owned(_)));
+/// assert_eq!(got, r#"a "quote""#);
+/// ```
+#[must_use]
+pub fn unescape<'a>(input: &'a str, escapes: &Escapes<'_>) -> Cow<'a, str> {
+    translate(input, escapes, true)
+}
+
+/// Replaces each occurrence of the raw string of a pair with its escaped form (or, when
+/// unescaping, the other way around), scanning left to right without rescanning replacements.
+fn translate<'a>(input: &'a str, escapes: &Escapes<'_>, unescaping: bool) -> Cow<'a, str> {
+    let mut out: Option<String> = None;
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < input.len() {
+        let rest = &input[i..];
+        let found = escapes
+            .pairs
+            .iter()
+            .map(|&(raw, escaped)| {
+                if unescaping {
+                    (escaped, raw)
+                } else {
+                    (raw, escaped)
+                }
+            })
+            .find(|(from, _)| !from.is_empty() && rest.starts_with(from));
+
+        if let Some((from, to)) = found {
+            let out = out.get_or_insert_with(|| String::with_capacity(input.len()));
+            out.push_str(&input[copied..i]);
+            out.push_str(to);
+            i += from.len();
+            copied = i;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    out.map_or(Borrowed(input), |mut out| {
+        out.push_str(&input[copied..]);
+        Owned(out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! escape_ok {
+        ($escapes:expr, $raw:expr, $escaped:expr, $is_borrow:expr) => {
+            let escapes = &$escapes;
+            let raw: &str = $raw;
+            let escaped: &str = $escaped;
+
+            let got = escape(raw, escapes);
+            assert_eq!(got, escaped, "escape");
+            assert_eq!(matches!(got, Borrowed(_)), $is_borrow, "escape borrow");
+
+            let got = unescape(escaped, escapes);
+            assert_eq!(got, raw, "unescape");
+            assert_eq!(matches!(got, Borrowed(_)), $is_borrow, "unescape borrow");
+        };
+    }
+
+    #[test]
+    fn backslash() {
+        escape_ok!(Escapes::BACKSLASH, "", "", true);
+        escape_ok!(Escapes::BACKSLASH, "hello", "hello", true);
+        escape_ok!(Escapes::BACKSLASH, "a\nb", "a\\nb", false);
+        escape_ok!(Escapes::BACKSLASH, "\"", "\\\"", false);
+        escape_ok!(Escapes::BACKSLASH, "\\n", "\\\\n", false);
+        escape_ok!(Escapes::BACKSLASH, "tab\there\0", "tab\\there\\0", false);
+        escape_ok!(Escapes::BACKSLASH, "ünï\ncödé", "ünï\\ncödé", false);
+    }
+
+    #[test]
+    fn html() {
+        escape_ok!(Escapes::HTML, "plain text", "plain text", true);
+        escape_ok!(Escapes::HTML, "a < b && c", "a &lt; b &amp;&amp; c", false);
+        escape_ok!(Escapes::HTML, "&lt;", "&amp;lt;", false);
+        escape_ok!(Escapes::HTML, "'\"", "&#39;&quot;", false);
+    }
+
+    #[test]
+    fn unknown_escapes() {
+        assert!(matches!(
+            unescape("\\x and \\", &Escapes::BACKSLASH),
+            Borrowed("\\x and \\")
+        ));
+        assert!(matches!(
+            unescape("&nbsp;&", &Escapes::HTML),
+            Borrowed("&nbsp;&")
+        ));
+    }
+
+    #[test]
+    fn precedence() {
+        let escapes = Escapes::new(&[("", "x"), ("ab", "1"), ("a", "2")]);
+        assert_eq!(escape("aab", &escapes), "21");
+        assert_eq!(unescape("21", &escapes), "aab");
+    }
+}
@@ -0,0 +1,233 @@
+//! A minimal C API, enabled by the `ffi` feature, so that code in other languages can reuse
+//! strloin's borrow-or-concatenate logic.
+//!
+//! Build the crate as a `staticlib` or `cdylib` (for example, with
+//! `cargo rustc --features ffi --crate-type staticlib`) and declare the functions below on the C
+//! side:
+//!
+//! ```c
+//! typedef struct Strloin Strloin;
+//! typedef struct { size_t start; size_t end; } StrloinRange;
+//! typedef struct { const uint8_t *ptr; size_t len; bool owned; } StrloinResult;
+//!
+//! Strloin *strloin_new(const uint8_t *source, size_t len);
+//! StrloinResult strloin_from_ranges(const Strloin *strloin, const StrloinRange *ranges, size_t len);
+//! void strloin_result_free(StrloinResult result);
+//! void strloin_free(Strloin *strloin);
+//! ```
+//!
+//! None of these functions panic: invalid input is reported with a null pointer instead.
+
+use crate::cow::{Borrowed, Owned};
+use crate::strloin::Strloin;
+use std::ops::Range;
+use std::{ptr, slice, str};
+
+/// A range of bytes of the source string, equivalent to `start..end`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrloinRange {
+    /// The start of the range, inclusive.
+    pub start: usize,
+
+    /// The end of the range, exclusive.
+    pub end: usize,
+}
+
+/// The string extracted by [`strloin_from_ranges`], which is not nul-terminated.
+///
+/// If `owned` is `false`, then `ptr` points into the source string. Otherwise, it points to a
+/// newly allocated string that must be released with [`strloin_result_free`]. On error, `ptr` is
+/// null.
+#[repr(C)]
+#[derive(Debug)]
+pub struct StrloinResult {
+    /// The first byte of the string, or null on error.
+    pub ptr: *const u8,
+
+    /// The length of the string in bytes.
+    pub len: usize,
+
+    /// Whether the string was allocated rather than borrowed from the source string.
+    pub owned: bool,
+}
+
+impl StrloinResult {
+    const ERROR: Self = Self {
+        ptr: ptr::null(),
+        len: 0,
+        owned: false,
+    };
+}
+
+/// Constructs a new [`Strloin`] for the `len` bytes at `source`, returning null if they aren't
+/// valid UTF-8. A null `source` with a `len` of 0 is treated as the empty string.
+///
+/// # Safety
+///
+/// `source` must point to `len` readable bytes that outlive the returned [`Strloin`], which
+/// must be released with [`strloin_free`].
+#[no_mangle]
+pub unsafe extern "C" fn strloin_new(source: *const u8, len: usize) -> *mut Strloin<'static> {
+    let bytes = if source.is_null() {
+        if len != 0 {
+            return ptr::null_mut();
+        }
+        &[]
+    } else {
+        slice::from_raw_parts(source, len)
+    };
+
+    str::from_utf8(bytes).map_or(ptr::null_mut(), |source| {
+        Box::into_raw(Box::new(Strloin::new(source)))
+    })
+}
+
+/// Extracts the `len` ranges at `ranges` from the source string, as with
+/// [`Strloin::try_from_ranges`], borrowing if they form a single contiguous region.
+///
+/// Returns a [`StrloinResult`] with a null `ptr` if `strloin` is null or any range is inverted,
+/// out of bounds, or splits a character.
+///
+/// # Safety
+///
+/// `strloin` must be null or returned by [`strloin_new`] and not yet freed, and `ranges` must
+/// point to `len` readable [`StrloinRange`]s (or may be null if `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn strloin_from_ranges(
+    strloin: *const Strloin<'static>,
+    ranges: *const StrloinRange,
+    len: usize,
+) -> StrloinResult {
+    let Some(strloin) = strloin.as_ref() else {
+        return StrloinResult::ERROR;
+    };
+
+    let ranges: Vec<Range<usize>> = if len == 0 {
+        Vec::new()
+    } else if ranges.is_null() {
+        return StrloinResult::ERROR;
+    } else {
+        slice::from_raw_parts(ranges, len)
+            .iter()
+            .map(|range| range.start..range.end)
+            .collect()
+    };
+
+    match strloin.try_from_ranges(&ranges) {
+        Ok(Borrowed(borrowed)) => StrloinResult {
+            ptr: borrowed.as_ptr(),
+            len: borrowed.len(),
+            owned: false,
+        },
+        Ok(Owned(owned)) => {
+            let len = owned.len();
+            StrloinResult {
+                ptr: Box::into_raw(owned.into_boxed_str()).cast::<u8>(),
+                len,
+                owned: true,
+            }
+        }
+        Err(_) => StrloinResult::ERROR,
+    }
+}
+
+/// Releases the string of a [`StrloinResult`] if it is owned; borrowed and error results are
+/// left alone.
+///
+/// # Safety
+///
+/// `result` must have been returned by [`strloin_from_ranges`], and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn strloin_result_free(result: StrloinResult) {
+    if result.owned && !result.ptr.is_null() {
+        let owned = ptr::slice_from_raw_parts_mut(result.ptr.cast_mut(), result.len) as *mut str;
+        drop(Box::from_raw(owned));
+    }
+}
+
+/// Releases a [`Strloin`] returned by [`strloin_new`]. Does nothing if `strloin` is null.
+///
+/// # Safety
+///
+/// `strloin` must be null or returned by [`strloin_new`], and not already freed. Borrowed
+/// results from it remain valid, since they point into the caller's source string.
+#[no_mangle]
+pub unsafe extern "C" fn strloin_free(strloin: *mut Strloin<'static>) {
+    if !strloin.is_null() {
+        drop(Box::from_raw(strloin));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn extract(strloin: *const Strloin<'static>, ranges: &[StrloinRange]) -> StrloinResult {
+        strloin_from_ranges(strloin, ranges.as_ptr(), ranges.len())
+    }
+
+    unsafe fn as_str(result: &StrloinResult) -> &str {
+        str::from_utf8_unchecked(slice::from_raw_parts(result.ptr, result.len))
+    }
+
+    const fn range(start: usize, end: usize) -> StrloinRange {
+        StrloinRange { start, end }
+    }
+
+    #[test]
+    fn from_ranges() {
+        let source = "hello world";
+        unsafe {
+            let strloin = strloin_new(source.as_ptr(), source.len());
+            assert!(!strloin.is_null());
+
+            let result = extract(strloin, &[range(0, 5), range(5, 11)]);
+            assert_eq!((as_str(&result), result.owned), ("hello world", false));
+            assert_eq!(result.ptr, source.as_ptr());
+            strloin_result_free(result);
+
+            let result = extract(strloin, &[range(0, 5), range(6, 11)]);
+            assert_eq!((as_str(&result), result.owned), ("helloworld", true));
+            strloin_result_free(result);
+
+            let result = strloin_from_ranges(strloin, ptr::null(), 0);
+            assert_eq!((as_str(&result), result.owned), ("", false));
+
+            for ranges in [
+                &[range(0, 12)][..],
+                &[range(3, 1)],
+                &[range(0, 5), range(9, 20)],
+            ] {
+                let result = extract(strloin, ranges);
+                assert!(result.ptr.is_null());
+                strloin_result_free(result);
+            }
+            assert!(strloin_from_ranges(strloin, ptr::null(), 1).ptr.is_null());
+
+            strloin_free(strloin);
+        }
+    }
+
+    #[test]
+    fn new() {
+        unsafe {
+            assert!(strloin_new([0xff].as_ptr(), 1).is_null());
+            assert!(strloin_new(ptr::null(), 1).is_null());
+            assert!(strloin_from_ranges(ptr::null(), ptr::null(), 0)
+                .ptr
+                .is_null());
+
+            let strloin = strloin_new(ptr::null(), 0);
+            let result = extract(strloin, &[range(0, 0)]);
+            assert_eq!((as_str(&result), result.owned), ("", false));
+            strloin_free(strloin);
+            strloin_free(ptr::null_mut());
+
+            let source = "héllo";
+            let strloin = strloin_new(source.as_ptr(), source.len());
+            assert!(extract(strloin, &[range(0, 2)]).ptr.is_null());
+            strloin_free(strloin);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::ops::Range;
+
+/// An unsigned integer type that [`Ranges`](crate::Ranges) can store offsets as. Narrower types
+/// than `usize` save memory when many ranges are kept alive at once.
+pub trait RangeIndex: Copy + Ord + Default + fmt::Debug + fmt::Display {
+    /// Converts the offset to a `usize`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset doesn't fit in a `usize`, which can only happen for a `u32` offset on
+    /// a 16-bit target.
+    fn to_usize(self) -> usize;
+
+    /// Converts a `usize` to an offset, or `None` if it doesn't fit.
+    fn from_usize(index: usize) -> Option<Self>;
+}
+
+macro_rules! range_index {
+    ($($ty:ty),*) => {
+        $(
+            impl RangeIndex for $ty {
+                fn to_usize(self) -> usize {
+                    usize::try_from(self).expect("range offset doesn't fit in usize")
+                }
+
+                fn from_usize(index: usize) -> Option<Self> {
+                    Self::try_from(index).ok()
+                }
+            }
+        )*
+    };
+}
+
+range_index!(u8, u16, u32);
+
+impl RangeIndex for usize {
+    fn to_usize(self) -> usize {
+        self
+    }
+
+    fn from_usize(index: usize) -> Option<Self> {
+        Some(index)
+    }
+}
+
+/// Converts a range of any [`RangeIndex`] to a range of `usize`.
+pub fn widen<I: RangeIndex>(range: &Range<I>) -> Range<usize> {
+    range.start.to_usize()..range.end.to_usize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(u8::from_usize(255), Some(255));
+        assert_eq!(u8::from_usize(256), None);
+        assert_eq!(u16::from_usize(65_536), None);
+        assert_eq!(u32::from_usize(7), Some(7));
+        assert_eq!(usize::from_usize(usize::MAX), Some(usize::MAX));
+        assert_eq!(200_u8.to_usize(), 200);
+        assert_eq!(u16::MAX.to_usize(), 65_535);
+        #[cfg(not(target_pointer_width = "16"))]
+        assert_eq!(u32::MAX.to_usize(), 4_294_967_295);
+        assert_eq!(widen(&(3_u16..9)), 3..9);
+    }
+}
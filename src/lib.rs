@@ -41,10 +41,57 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 
+mod annotate;
+mod array_ranges;
+mod batch;
+mod builder;
 mod cow;
+mod cursor;
+mod diff;
+mod error;
+mod escape;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod index;
+mod named_ranges;
+mod offset;
+mod packed_ranges;
+mod policy;
+mod pool;
+mod provenance;
+mod range_ext;
 mod ranges;
+mod ranges_in;
+mod reader;
+mod selection;
+mod small_ranges;
 mod strloin;
+mod template;
+mod tokenizer;
 
-pub use crate::cow::{Borrowed, Cow, Owned};
-pub use crate::ranges::{collapse_ranges, Ranges};
-pub use crate::strloin::Strloin;
+pub use crate::annotate::Annotated;
+pub use crate::array_ranges::ArrayRanges;
+pub use crate::batch::{Batch, BatchIter};
+pub use crate::builder::SelectionBuilder;
+pub use crate::cow::{Borrowed, Cow, CowStrExt, Owned};
+pub use crate::cursor::{Cursor, CursorCheckpoint};
+pub use crate::error::Error;
+pub use crate::escape::{escape, unescape, Escapes};
+pub use crate::index::RangeIndex;
+pub use crate::named_ranges::NamedRanges;
+pub use crate::offset::OffsetStrloin;
+pub use crate::packed_ranges::{PackedIter, PackedRanges};
+pub use crate::policy::CollapsePolicy;
+pub use crate::pool::{Pooled, PooledStrloin, StringPool};
+pub use crate::provenance::ProvenanceCow;
+pub use crate::range_ext::RangeExt;
+pub use crate::ranges::{
+    collapse_ranges, collapse_ranges_iter, normalize_ranges, Checkpoint, Gaps, Ranges,
+};
+pub use crate::ranges_in::RangesIn;
+pub use crate::reader::Reader;
+pub use crate::selection::{CharIndices, Chars, Segments, Selection, Split};
+pub use crate::small_ranges::SmallRanges;
+pub use crate::strloin::{LinesRanges, SplitRanges, Strloin};
+pub use crate::template::Template;
+pub use crate::tokenizer::{Token, Tokenizer};
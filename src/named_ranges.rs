@@ -0,0 +1,191 @@
+use crate::ranges::Ranges;
+use std::ops::Range;
+use std::slice::Iter;
+
+/// A collection of [`Ranges`] labeled by key, like the named capture groups of a regex.
+///
+/// Extract every label at once with
+/// [`Strloin::from_named_ranges`](crate::Strloin::from_named_ranges). Keys are kept in the order
+/// they were first added.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{NamedRanges, Strloin};
+///
+/// let strloin = Strloin::new("key = some value");
+/// let mut named = NamedRanges::new();
+/// named.push("key", 0..3);
+/// named.push("value", 6..10);
+/// named.push("value", 10..16);
+///
+/// let got = strloin.from_named_ranges(&named);
+/// assert_eq!(got["key"], "key");
+/// assert_eq!(got["value"], "some value"); // borrowed
+/// ```
+#[derive(Debug, Clone)]
+pub struct NamedRanges<K> {
+    entries: Vec<(K, Ranges)>,
+}
+
+impl<K> NamedRanges<K> {
+    /// Construct a new empty [`NamedRanges`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the number of keys.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over each key and its ranges, in the order the keys were first added.
+    pub fn iter(&self) -> Iter<'_, (K, Ranges)> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the keys, in the order they were first added.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+}
+
+impl<K: PartialEq> NamedRanges<K> {
+    /// Adds a range to the [`Ranges`] for `key`, collapsing if possible, as with
+    /// [`Ranges::push`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::NamedRanges;
+    ///
+    /// let mut named = NamedRanges::new();
+    /// named.push('a', 0..2);
+    /// named.push('b', 4..6);
+    /// named.push('a', 2..3);
+    /// assert_eq!(named.get(&'a').unwrap().ranges, vec![0..3]);
+    /// ```
+    pub fn push(&mut self, key: K, range: Range<usize>) {
+        if let Some(ranges) = self.get_mut(&key) {
+            ranges.push(range);
+        } else {
+            self.entries.push((key, Ranges::from(range)));
+        }
+    }
+
+    /// Sets the [`Ranges`] for `key`, returning the ranges it replaced, if any.
+    pub fn insert(&mut self, key: K, ranges: Ranges) -> Option<Ranges> {
+        if let Some(existing) = self.get_mut(&key) {
+            return Some(std::mem::replace(existing, ranges));
+        }
+
+        self.entries.push((key, ranges));
+        None
+    }
+
+    /// Returns the [`Ranges`] for `key`, if any.
+    #[must_use]
+    pub fn get(&self, key: &K) -> Option<&Ranges> {
+        self.entries
+            .iter()
+            .find_map(|(k, ranges)| (k == key).then_some(ranges))
+    }
+
+    /// Returns the [`Ranges`] for `key` mutably, if any.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut Ranges> {
+        self.entries
+            .iter_mut()
+            .find_map(|(k, ranges)| (k == key).then_some(ranges))
+    }
+
+    /// Removes `key`, returning its [`Ranges`], if any.
+    pub fn remove(&mut self, key: &K) -> Option<Ranges> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+impl<K> Default for NamedRanges<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialEq> FromIterator<(K, Range<usize>)> for NamedRanges<K> {
+    fn from_iter<T: IntoIterator<Item = (K, Range<usize>)>>(iter: T) -> Self {
+        let mut named = Self::new();
+        named.extend(iter);
+        named
+    }
+}
+
+impl<K: PartialEq> Extend<(K, Range<usize>)> for NamedRanges<K> {
+    fn extend<T: IntoIterator<Item = (K, Range<usize>)>>(&mut self, iter: T) {
+        for (key, range) in iter {
+            self.push(key, range);
+        }
+    }
+}
+
+impl<'n, K> IntoIterator for &'n NamedRanges<K> {
+    type Item = &'n (K, Ranges);
+    type IntoIter = Iter<'n, (K, Ranges)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push() {
+        let named: NamedRanges<&str> = [("a", 0..2), ("b", 4..6), ("a", 2..3), ("a", 7..8)]
+            .into_iter()
+            .collect();
+        assert_eq!(named.len(), 2);
+        assert_eq!(named.keys().collect::<Vec<_>>(), [&"a", &"b"]);
+        assert_eq!(
+            named.get(&"a").map(|r| r.ranges.clone()),
+            Some(vec![0..3, 7..8])
+        );
+        assert_eq!(named.get(&"b").map(|r| r.ranges.clone()), Some(vec![4..6]));
+        assert!(named.get(&"c").is_none());
+    }
+
+    #[test]
+    fn edit() {
+        let mut named = NamedRanges::default();
+        assert!(named.is_empty());
+        assert!(named.insert(1, Ranges::from(0..2)).is_none());
+        named.push(2, 3..4);
+
+        let replaced = named.insert(1, Ranges::from(5..6));
+        assert_eq!(replaced.map(|r| r.ranges), Some(vec![0..2]));
+        assert_eq!(named.keys().collect::<Vec<_>>(), [&1, &2]);
+
+        named.get_mut(&2).expect("key").push(4..5);
+        assert_eq!(named.remove(&2).map(|r| r.ranges), Some(vec![3..5]));
+        assert!(named.remove(&2).is_none());
+        assert_eq!(
+            (&named)
+                .into_iter()
+                .map(|(k, r)| (*k, r.ranges.clone()))
+                .collect::<Vec<_>>(),
+            [(1, vec![5..6])]
+        );
+    }
+}
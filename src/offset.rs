@@ -0,0 +1,204 @@
+use crate::cow::{Borrowed, Cow, Owned};
+use crate::error::Error;
+use crate::strloin::Strloin;
+use std::ops::Range;
+use std::slice;
+
+/// A source string that is a slice of a larger document starting at byte `offset`, so that
+/// ranges can be given in the coordinates of the whole document.
+///
+/// The offset is subtracted from every range before slicing, and added back to the ranges
+/// reported in errors.
+///
+/// Construct one with [`Strloin::with_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, Strloin};
+///
+/// let document = "header\nhello world";
+/// let body = Strloin::new(&document[7..]).with_offset(7);
+///
+/// assert!(matches!(body.from_ranges(&[7..12]), Borrowed("hello")));
+/// assert_eq!(body.from_ranges(&[13..18, 12..13, 7..12]), "world hello");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetStrloin<'a> {
+    pub source: &'a str,
+    pub offset: usize,
+}
+
+impl<'a> OffsetStrloin<'a> {
+    /// Returns a [`Strloin`] for the source string, which takes ranges relative to its start
+    /// instead of the document's.
+    #[must_use]
+    pub const fn strloin(&self) -> Strloin<'a> {
+        Strloin::new(self.source)
+    }
+
+    /// Translates a range in document coordinates into one relative to the source string, or
+    /// `None` if it starts or ends before the offset.
+    #[must_use]
+    pub fn to_local(&self, range: &Range<usize>) -> Option<Range<usize>> {
+        Some(range.start.checked_sub(self.offset)?..range.end.checked_sub(self.offset)?)
+    }
+
+    /// Translates a range relative to the source string, like those returned by
+    /// [`Strloin::split_ranges`], into document coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if adding the offset overflows `usize`.
+    #[must_use]
+    pub const fn to_global(&self, range: &Range<usize>) -> Range<usize> {
+        range.start + self.offset..range.end + self.offset
+    }
+
+    /// Extracts a string from the given ranges in document coordinates, as with
+    /// [`Strloin::from_ranges`]; if the ranges form a single contiguous region, then the result
+    /// will borrow from the source string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range starts before the offset, or can't be sliced from the source string.
+    #[must_use]
+    pub fn from_ranges(&self, ranges: &[Range<usize>]) -> Cow<'a, str> {
+        let local = ranges.iter().map(|range| {
+            self.to_local(range).unwrap_or_else(|| {
+                panic!("range {:?} starts before the offset {}", range, self.offset)
+            })
+        });
+
+        self.strloin().collapse(local.clone()).map_or_else(
+            || Owned(self.strloin().concat_into(String::new(), "", local)),
+            |range| Borrowed(&self.source[range]),
+        )
+    }
+
+    /// Extracts a string from the given ranges in document coordinates as with
+    /// [`OffsetStrloin::from_ranges`], but checks them first, as with
+    /// [`Strloin::try_from_ranges`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Overflow`] for a range that starts or ends before the offset, and
+    /// otherwise the errors of [`Strloin::check_ranges`], with their ranges and positions in
+    /// document coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Strloin};
+    ///
+    /// let body = Strloin::new("héllo").with_offset(10);
+    /// assert_eq!(body.try_from_ranges(&[10..11, 15..16]).unwrap(), "ho");
+    /// assert_eq!(
+    ///     body.try_from_ranges(&[10..12]),
+    ///     Err(Error::NotCharBoundary { index: 0, pos: 12 }),
+    /// );
+    /// assert_eq!(
+    ///     body.try_from_ranges(&[10..17]),
+    ///     Err(Error::OutOfBounds { index: 0, range: 10..17, len: 16 }),
+    /// );
+    /// ```
+    pub fn try_from_ranges(&self, ranges: &[Range<usize>]) -> Result<Cow<'a, str>, Error> {
+        for (index, range) in ranges.iter().enumerate() {
+            let local = self.to_local(range).ok_or_else(|| Error::Overflow {
+                index,
+                range: range.clone(),
+            })?;
+            self.strloin()
+                .check_ranges(slice::from_ref(&local))
+                .map_err(|error| self.globalize(index, range, error))?;
+        }
+
+        Ok(self.from_ranges(ranges))
+    }
+
+    /// Translates an error from checking one range in isolation back into document coordinates.
+    fn globalize(&self, index: usize, range: &Range<usize>, error: Error) -> Error {
+        let range = range.clone();
+        match error {
+            Error::Inverted { .. } => Error::Inverted { index, range },
+            Error::OutOfBounds { len, .. } => Error::OutOfBounds {
+                index,
+                range,
+                len: len + self.offset,
+            },
+            Error::NotCharBoundary { pos, .. } => Error::NotCharBoundary {
+                index,
+                pos: pos + self.offset,
+            },
+            error => error,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_ranges() {
+        let document = "say: hello world";
+        let body = Strloin::new(&document[5..]).with_offset(5);
+        let strloin = Strloin::new(document);
+
+        for ranges in [
+            &[][..],
+            &[5..5],
+            &[5..10],
+            &[5..10, 10..16],
+            &[11..16, 10..11, 5..10],
+            &[5..5, 11..16],
+        ] {
+            let expected = strloin.from_ranges(ranges);
+            let got = body.from_ranges(ranges);
+            assert_eq!(got, expected);
+            assert_eq!(matches!(got, Borrowed(_)), matches!(expected, Borrowed(_)));
+            assert_eq!(body.try_from_ranges(ranges), Ok(expected));
+        }
+
+        assert_eq!(body.to_local(&(7..9)), Some(2..4));
+        assert_eq!(body.to_local(&(4..9)), None);
+        assert_eq!(body.to_global(&(2..4)), 7..9);
+        assert_eq!(body.strloin().from_ranges(&[0..5]), "hello");
+    }
+
+    #[test]
+    #[should_panic(expected = "range 4..6 starts before the offset 5")]
+    fn from_ranges_before_offset() {
+        let _ = Strloin::new("hello")
+            .with_offset(5)
+            .from_ranges(&[5..6, 4..6]);
+    }
+
+    #[test]
+    fn try_from_ranges() {
+        let body = Strloin::new("hello").with_offset(5);
+        assert_eq!(
+            body.try_from_ranges(&[5..6, 4..6]),
+            Err(Error::Overflow {
+                index: 1,
+                range: 4..6
+            })
+        );
+        assert_eq!(
+            body.try_from_ranges(&[8..6]),
+            Err(Error::Inverted {
+                index: 0,
+                range: 8..6
+            })
+        );
+        assert_eq!(
+            body.try_from_ranges(&[5..6, 9..11]),
+            Err(Error::OutOfBounds {
+                index: 1,
+                range: 9..11,
+                len: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error::Error;
+use std::iter::{FusedIterator, Zip};
+use std::ops::Range;
+use std::slice::Iter as SliceIter;
+
+/// A compact alternative to [`Ranges`](crate::Ranges) for very large lists of ranges, storing
+/// starts and lengths as `u32` in separate arrays.
+///
+/// Each range takes 8 bytes rather than 16 (on 64-bit targets), and extracting with
+/// [`Strloin::from_packed_ranges`](crate::Strloin::from_packed_ranges) walks both arrays
+/// sequentially. Offsets must fit in a `u32`.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{PackedRanges, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+/// let mut ranges = PackedRanges::new();
+/// ranges.push(0..5);
+/// ranges.push(6..11);
+///
+/// assert_eq!(ranges.len(), 2);
+/// assert_eq!(strloin.from_packed_ranges(&ranges), "helloworld");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackedRanges {
+    starts: Vec<u32>,
+    lens: Vec<u32>,
+}
+
+impl PackedRanges {
+    /// Construct a new empty [`PackedRanges`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            starts: Vec::new(),
+            lens: Vec::new(),
+        }
+    }
+
+    /// Construct a new empty [`PackedRanges`] with the given capacity.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            starts: Vec::with_capacity(capacity),
+            lens: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Adds a new range, collapsing it into the last range if possible, just like
+    /// [`Ranges::push`](crate::Ranges::push).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends or doesn't fit in a `u32`. See
+    /// [`PackedRanges::try_push`] for a non-panicking version.
+    pub fn push(&mut self, range: Range<usize>) {
+        if let Err(e) = self.try_push(range) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Adds a new range, collapsing it into the last range if possible, or returns an error if
+    /// it can't be stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if the range starts after it ends, and [`Error::Overflow`]
+    /// if it ends past `u32::MAX`. In either case the range is not added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, PackedRanges};
+    ///
+    /// let mut ranges = PackedRanges::new();
+    /// assert_eq!(ranges.try_push(0..5), Ok(()));
+    /// assert_eq!(ranges.try_push(5..8), Ok(()));
+    /// assert_eq!(ranges.try_push(9..7), Err(Error::Inverted { index: 1, range: 9..7 }));
+    /// assert_eq!(ranges.iter().collect::<Vec<_>>(), [0..8]);
+    /// ```
+    pub fn try_push(&mut self, range: Range<usize>) -> Result<(), Error> {
+        let index = self.len();
+        if range.start > range.end {
+            return Err(Error::Inverted { index, range });
+        }
+        let (Ok(start), Ok(end)) = (u32::try_from(range.start), u32::try_from(range.end)) else {
+            return Err(Error::Overflow { index, range });
+        };
+
+        if let (Some(last_start), Some(last_len)) = (self.starts.last_mut(), self.lens.last_mut()) {
+            if start == end {
+                return Ok(());
+            }
+            if *last_len == 0 {
+                *last_start = start;
+                *last_len = end - start;
+                return Ok(());
+            }
+            if start == *last_start + *last_len {
+                *last_len += end - start;
+                return Ok(());
+            }
+        }
+
+        self.starts.push(start);
+        self.lens.push(end - start);
+        Ok(())
+    }
+
+    /// Returns the number of ranges stored, after collapsing.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns `true` if no ranges are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// Returns the total number of bytes selected by all ranges, which is the length of the
+    /// string [`Strloin::from_packed_ranges`](crate::Strloin::from_packed_ranges) would produce.
+    #[must_use]
+    pub fn total_len(&self) -> usize {
+        self.lens.iter().map(|&len| len as usize).sum()
+    }
+
+    /// Returns the range at position `index`, after collapsing, or `None` if out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(index)? as usize;
+        Some(start..start + self.lens[index] as usize)
+    }
+
+    /// Returns an iterator over the stored ranges, after collapsing.
+    #[must_use]
+    pub fn iter(&self) -> PackedIter<'_> {
+        PackedIter {
+            inner: self.starts.iter().zip(self.lens.iter()),
+        }
+    }
+
+    /// Removes all ranges.
+    pub fn clear(&mut self) {
+        self.starts.clear();
+        self.lens.clear();
+    }
+
+    /// Removes the last range and returns it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<Range<usize>> {
+        let start = self.starts.pop()? as usize;
+        let len = self.lens.pop().unwrap_or_default() as usize;
+        Some(start..start + len)
+    }
+}
+
+impl<'r> IntoIterator for &'r PackedRanges {
+    type Item = Range<usize>;
+    type IntoIter = PackedIter<'r>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the ranges of a [`PackedRanges`], created by [`PackedRanges::iter`].
+#[derive(Debug, Clone)]
+pub struct PackedIter<'r> {
+    inner: Zip<SliceIter<'r, u32>, SliceIter<'r, u32>>,
+}
+
+impl Iterator for PackedIter<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&start, &len) = self.inner.next()?;
+        Some(start as usize..start as usize + len as usize)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for PackedIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (&start, &len) = self.inner.next_back()?;
+        Some(start as usize..start as usize + len as usize)
+    }
+}
+
+impl ExactSizeIterator for PackedIter<'_> {}
+
+impl FusedIterator for PackedIter<'_> {}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+    use crate::Ranges;
+
+    #[test]
+    fn push() {
+        macro_rules! push_ok {
+            ($input:expr) => {
+                let input: &[Range<usize>] = &$input;
+                let mut packed = PackedRanges::new();
+                for range in input {
+                    packed.push(range.clone());
+                }
+                let expected: Ranges = input.iter().cloned().collect();
+                assert_eq!(packed.iter().collect::<Vec<_>>(), expected.ranges);
+                assert_eq!(packed.len(), expected.len());
+                assert_eq!(packed.total_len(), expected.total_len());
+            };
+        }
+
+        push_ok!([]);
+        push_ok!([0..5]);
+        push_ok!([0..5, 5..11]);
+        push_ok!([0..5, 6..11]);
+        push_ok!([0..0, 0..5, 5..5, 5..8]);
+        push_ok!([6..11, 5..6, 0..5]);
+    }
+
+    #[test]
+    fn try_push() {
+        let mut packed = PackedRanges::with_capacity(2);
+        let max = u32::MAX as usize;
+        assert_eq!(packed.try_push(max..max), Ok(()));
+        assert_eq!(
+            packed.try_push(max..max + 1),
+            Err(Error::Overflow {
+                index: 1,
+                range: max..max + 1
+            })
+        );
+        assert_eq!(
+            packed.try_push(4..3),
+            Err(Error::Inverted {
+                index: 1,
+                range: 4..3
+            })
+        );
+        assert_eq!(packed.iter().collect::<Vec<_>>(), [max..max]);
+    }
+
+    #[test]
+    #[should_panic(expected = "starts after it ends")]
+    fn push_inverted() {
+        PackedRanges::new().push(4..3);
+    }
+
+    #[test]
+    fn edit() {
+        let mut packed = PackedRanges::default();
+        packed.push(0..2);
+        packed.push(3..5);
+        assert_eq!(packed.get(1), Some(3..5));
+        assert_eq!(packed.get(2), None);
+        assert_eq!(packed.iter().rev().collect::<Vec<_>>(), [3..5, 0..2]);
+        assert_eq!((&packed).into_iter().len(), 2);
+
+        assert_eq!(packed.pop(), Some(3..5));
+        assert_eq!(packed.len(), 1);
+        packed.clear();
+        assert_eq!(packed.pop(), None);
+        assert!(packed.is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+use crate::error::Error;
+use crate::index::RangeIndex;
+use std::ops::Range;
+
+/// Controls how ranges collapse, for use with [`Ranges::try_push_with`] and
+/// [`Strloin::from_ranges_with`]. The default policy collapses exactly as [`Ranges::push`] and
+/// [`Strloin::from_ranges`] do.
+///
+/// [`Ranges::try_push_with`]: crate::Ranges::try_push_with
+/// [`Ranges::push`]: crate::Ranges::push
+/// [`Strloin::from_ranges_with`]: crate::Strloin::from_ranges_with
+/// [`Strloin::from_ranges`]: crate::Strloin::from_ranges
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{CollapsePolicy, Strloin};
+///
+/// const LENIENT: CollapsePolicy = CollapsePolicy {
+///     merge_overlapping: true,
+///     ..CollapsePolicy::new()
+/// };
+///
+/// let strloin = Strloin::new("hello world");
+///
+/// assert_eq!(strloin.from_ranges(&[0..5, 3..11]), "hellolo world");
+/// assert_eq!(strloin.from_ranges_with(&[0..5, 3..11], &LENIENT).unwrap(), "hello world");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollapsePolicy {
+    /// Whether empty ranges are ignored, rather than only collapsing with a range they touch.
+    pub ignore_empty: bool,
+
+    /// Whether a range that overlaps the end of the last range and extends past it merges into
+    /// it, rather than selecting the overlapping bytes again.
+    pub merge_overlapping: bool,
+
+    /// Whether an invalid range panics, rather than returning an error.
+    pub panic_on_invalid: bool,
+}
+
+impl CollapsePolicy {
+    /// Construct the default [`CollapsePolicy`], which ignores empty ranges, doesn't merge
+    /// overlapping ranges, and returns an error for invalid ranges.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            ignore_empty: true,
+            merge_overlapping: false,
+            panic_on_invalid: false,
+        }
+    }
+
+    /// Checks the range at `index` against the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if the range starts after it ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics instead of returning an error if `panic_on_invalid` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{CollapsePolicy, Error};
+    ///
+    /// let policy = CollapsePolicy::new();
+    /// assert_eq!(policy.validate(0, &(2..5)), Ok(()));
+    /// assert_eq!(policy.validate(1, &(5..2)), Err(Error::Inverted { index: 1, range: 5..2 }));
+    /// ```
+    pub fn validate(&self, index: usize, range: &Range<usize>) -> Result<(), Error> {
+        if range.start <= range.end {
+            return Ok(());
+        }
+
+        let error = Error::Inverted {
+            index,
+            range: range.clone(),
+        };
+        assert!(!self.panic_on_invalid, "{}", error);
+        Err(error)
+    }
+
+    /// Merges `range` into `last` if the policy lets it collapse, returning `true` if so.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::CollapsePolicy;
+    ///
+    /// let policy = CollapsePolicy::new();
+    /// let mut last = 0..5_usize;
+    /// assert!(policy.merge(&mut last, &(5..8)));
+    /// assert!(!policy.merge(&mut last, &(6..11)));
+    /// assert_eq!(last, 0..8);
+    /// ```
+    #[allow(clippy::suspicious_operation_groupings)]
+    pub fn merge<I: RangeIndex>(&self, last: &mut Range<I>, range: &Range<I>) -> bool {
+        if self.ignore_empty {
+            if range.start == range.end {
+                return true;
+            }
+            if last.start == last.end {
+                *last = range.clone();
+                return true;
+            }
+        }
+
+        if last.start > last.end || range.start > range.end {
+            return false;
+        }
+
+        let overlaps = last.start <= range.start && range.start < last.end && last.end < range.end;
+        if range.start == last.end || (self.merge_overlapping && overlaps) {
+            last.end = range.end;
+            return true;
+        }
+        false
+    }
+
+    /// Collapses a slice of ranges into a single contiguous range under the policy, if possible.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if any range starts after it ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics instead of returning an error if `panic_on_invalid` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::CollapsePolicy;
+    ///
+    /// let strict = CollapsePolicy {
+    ///     ignore_empty: false,
+    ///     ..CollapsePolicy::new()
+    /// };
+    ///
+    /// assert_eq!(CollapsePolicy::new().collapse(&[0..5, 7..7, 5..11]), Ok(Some(0..11)));
+    /// assert_eq!(strict.collapse(&[0..5, 7..7, 5..11]), Ok(None));
+    /// ```
+    pub fn collapse(&self, ranges: &[Range<usize>]) -> Result<Option<Range<usize>>, Error> {
+        let mut collapsed: Option<Range<usize>> = None;
+        let mut contiguous = true;
+
+        for (index, range) in ranges.iter().enumerate() {
+            self.validate(index, range)?;
+            match &mut collapsed {
+                None => collapsed = Some(range.clone()),
+                Some(last) => contiguous &= self.merge(last, range),
+            }
+        }
+
+        Ok(contiguous.then(|| collapsed.unwrap_or(0..0)))
+    }
+}
+
+impl Default for CollapsePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+
+    const LENIENT: CollapsePolicy = CollapsePolicy {
+        ignore_empty: true,
+        merge_overlapping: true,
+        panic_on_invalid: false,
+    };
+
+    const STRICT: CollapsePolicy = CollapsePolicy {
+        ignore_empty: false,
+        merge_overlapping: false,
+        panic_on_invalid: true,
+    };
+
+    #[test]
+    fn merge() {
+        macro_rules! merge_ok {
+            ($policy:expr, $last:expr, $range:expr, $expected:expr) => {
+                let mut last = $last;
+                let merged = $policy.merge(&mut last, &$range);
+                let expected: Option<std::ops::Range<usize>> = $expected;
+                assert_eq!(merged.then(|| last), expected, "{:?}", $policy);
+            };
+        }
+
+        for policy in [CollapsePolicy::new(), LENIENT, STRICT] {
+            merge_ok!(policy, 0..5, 5..8, Some(0..8));
+            merge_ok!(policy, 0..5, 6..8, None);
+            merge_ok!(policy, 0..5, 5..2, None);
+            merge_ok!(policy, 5..2, 2..8, None);
+        }
+
+        merge_ok!(CollapsePolicy::new(), 0..5, 7..7, Some(0..5));
+        merge_ok!(CollapsePolicy::new(), 3..3, 0..5, Some(0..5));
+        merge_ok!(STRICT, 0..5, 7..7, None);
+        merge_ok!(STRICT, 0..5, 5..5, Some(0..5));
+        merge_ok!(STRICT, 3..3, 0..5, None);
+        merge_ok!(STRICT, 0..0, 0..5, Some(0..5));
+
+        merge_ok!(CollapsePolicy::new(), 0..5, 3..8, None);
+        merge_ok!(LENIENT, 0..5, 3..8, Some(0..8));
+        merge_ok!(LENIENT, 0..5, 0..8, Some(0..8));
+        merge_ok!(LENIENT, 0..5, 3..5, None);
+        merge_ok!(LENIENT, 0..5, 1..3, None);
+        merge_ok!(LENIENT, 2..5, 1..8, None);
+    }
+
+    #[test]
+    fn collapse() {
+        let policy = CollapsePolicy::default();
+        assert_eq!(policy.collapse(&[]), Ok(Some(0..0)));
+        assert_eq!(policy.collapse(&[0..5, 5..5, 5..8]), Ok(Some(0..8)));
+        assert_eq!(policy.collapse(&[0..5, 6..8]), Ok(None));
+        assert_eq!(
+            policy.collapse(&[0..5, 6..8, 9..7]),
+            Err(Error::Inverted {
+                index: 2,
+                range: 9..7
+            })
+        );
+
+        assert_eq!(LENIENT.collapse(&[0..5, 3..8, 8..11]), Ok(Some(0..11)));
+        assert_eq!(STRICT.collapse(&[0..0, 2..5]), Ok(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "range 9..7 at index 1 starts after it ends")]
+    fn panic_on_invalid() {
+        let _ = STRICT.collapse(&[0..5, 9..7]);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::cow::{Borrowed, Cow, Owned};
+use crate::strloin::Strloin;
+use std::fmt;
+use std::ops::{Deref, Range};
+use std::sync::{Mutex, PoisonError};
+
+/// A pool of `String` buffers for reuse by the owned results of a [`PooledStrloin`].
+///
+/// This keeps a long-running process that extracts discontiguous ranges from allocating a new
+/// string each time. A [`Pooled`] result hands its buffer back to the pool when it's dropped.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{StringPool, Strloin};
+///
+/// let pool = StringPool::new();
+/// let strloin = Strloin::new("hello world");
+/// let pooled = strloin.with_pool(&pool);
+///
+/// let got = pooled.from_ranges(&[0..5, 6..11]);
+/// assert_eq!(got, "helloworld");
+/// drop(got);
+/// assert_eq!(pool.len(), 1);
+///
+/// let got = pooled.from_ranges(&[6..11, 0..5]); // reuses the buffer
+/// assert_eq!(got, "worldhello");
+/// assert_eq!(pool.len(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct StringPool {
+    buffers: Mutex<Vec<String>>,
+}
+
+impl StringPool {
+    /// Construct a new, empty [`StringPool`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of buffers waiting in the pool to be reused.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.buffers().len()
+    }
+
+    /// Returns `true` if there are no buffers waiting in the pool.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes an empty buffer out of the pool, keeping the capacity of its previous use, or a new
+    /// one if the pool is empty.
+    #[must_use]
+    pub fn take(&self) -> String {
+        self.buffers().pop().unwrap_or_default()
+    }
+
+    /// Clears a buffer and adds it to the pool for reuse.
+    pub fn put(&self, mut buffer: String) {
+        buffer.clear();
+        self.buffers().push(buffer);
+    }
+
+    /// Locks the buffers. Nothing can panic while the lock is held, so a poisoned lock is still
+    /// consistent and is recovered rather than propagated.
+    fn buffers(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A source string paired with a [`StringPool`] whose buffers are used for owned results.
+///
+/// Construct one with [`Strloin::with_pool`].
+#[derive(Debug, Clone, Copy)]
+pub struct PooledStrloin<'a, 'p> {
+    pub source: &'a str,
+    pub pool: &'p StringPool,
+}
+
+impl<'a, 'p> PooledStrloin<'a, 'p> {
+    /// Extracts a string from the given ranges, as with [`Strloin::from_ranges`]; if the ranges
+    /// don't form a single contiguous region, then they will be collected into a buffer taken
+    /// from the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{StringPool, Strloin};
+    ///
+    /// let pool = StringPool::new();
+    /// let strloin = Strloin::new("hello world");
+    /// let pooled = strloin.with_pool(&pool);
+    ///
+    /// assert!(pooled.from_ranges(&[0..5, 5..11]).is_borrowed());
+    /// assert_eq!(pooled.from_ranges(&[0..5, 6..11]).len(), 10);
+    /// ```
+    #[must_use]
+    pub fn from_ranges(&self, ranges: &[Range<usize>]) -> Pooled<'a, 'p> {
+        let strloin = Strloin::new(self.source);
+        let (head, index) = strloin.contiguous_prefix(ranges);
+        if index == ranges.len() {
+            return Pooled::new(Borrowed(head), self.pool);
+        }
+
+        let owned = strloin.concat_into(self.pool.take(), head, ranges[index..].iter().cloned());
+        Pooled::new(Owned(owned), self.pool)
+    }
+}
+
+/// The result of [`PooledStrloin::from_ranges`], which derefs to `str`. When an owned result is
+/// dropped, its buffer goes back to the pool.
+pub struct Pooled<'a, 'p> {
+    cow: Cow<'a, str>,
+    pool: &'p StringPool,
+}
+
+impl<'a, 'p> Pooled<'a, 'p> {
+    const fn new(cow: Cow<'a, str>, pool: &'p StringPool) -> Self {
+        Pooled { cow, pool }
+    }
+
+    /// Returns `true` if the result borrows from the source string.
+    #[must_use]
+    pub const fn is_borrowed(&self) -> bool {
+        matches!(self.cow, Borrowed(_))
+    }
+
+    /// Converts the result into a [`Cow`], keeping its buffer out of the pool.
+    #[must_use]
+    pub fn into_cow(mut self) -> Cow<'a, str> {
+        std::mem::replace(&mut self.cow, Borrowed(""))
+    }
+}
+
+impl Deref for Pooled<'_, '_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.cow
+    }
+}
+
+impl AsRef<str> for Pooled<'_, '_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Drop for Pooled<'_, '_> {
+    fn drop(&mut self) {
+        if let Owned(buffer) = std::mem::replace(&mut self.cow, Borrowed("")) {
+            self.pool.put(buffer);
+        }
+    }
+}
+
+impl fmt::Debug for Pooled<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.cow, f)
+    }
+}
+
+impl fmt::Display for Pooled<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl PartialEq<str> for Pooled<'_, '_> {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for Pooled<'_, '_> {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_ranges() {
+        macro_rules! from_ranges_ok {
+            ($input:expr) => {
+                let strloin = Strloin::new("hello world");
+                let pool = StringPool::new();
+                let input: &[Range<usize>] = &$input;
+
+                let expected = strloin.from_ranges(input);
+                let got = strloin.with_pool(&pool).from_ranges(input);
+                assert_eq!(got, &*expected);
+                assert_eq!(got.is_borrowed(), matches!(expected, Borrowed(_)));
+
+                let borrowed = got.is_borrowed();
+                drop(got);
+                assert_eq!(pool.len(), usize::from(!borrowed), "returned to the pool");
+            };
+        }
+
+        from_ranges_ok!([]);
+        from_ranges_ok!([0..5]);
+        from_ranges_ok!([0..5, 5..11]);
+        from_ranges_ok!([0..5, 6..11]);
+        from_ranges_ok!([6..11, 5..6, 0..5]);
+        from_ranges_ok!([0..5, 7..7, 5..6, 0..5]);
+    }
+
+    #[test]
+    fn reuse() {
+        let strloin = Strloin::new("hello world");
+        let pool = StringPool::new();
+        let pooled = strloin.with_pool(&pool);
+
+        let first = pooled.from_ranges(&[0..5, 6..11]);
+        let second = pooled.from_ranges(&[6..11, 0..5]);
+        assert_eq!((&*first, &*second), ("helloworld", "worldhello"));
+        drop(first);
+        drop(second);
+        assert_eq!(pool.len(), 2);
+
+        let third = pooled.from_ranges(&[0..1, 10..11]);
+        assert_eq!(third, "hd");
+        assert_eq!(pool.len(), 1);
+
+        let Owned(buffer) = third.into_cow() else {
+            panic!("expected owned");
+        };
+        assert_eq!((buffer.as_str(), buffer.capacity() >= 10), ("hd", true));
+        assert_eq!(pool.len(), 1, "into_cow keeps the buffer");
+    }
+
+    #[test]
+    fn take_put() {
+        let pool = StringPool::new();
+        assert!(pool.is_empty());
+        assert_eq!(pool.take(), "");
+
+        pool.put("leftover".to_owned());
+        assert_eq!(pool.len(), 1);
+        let buffer = pool.take();
+        assert_eq!((buffer.as_str(), buffer.capacity() >= 8), ("", true));
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::cow::{Borrowed, Cow};
+use crate::ranges::Ranges;
+use crate::selection::Selection;
+use std::fmt;
+use std::ops::Deref;
+
+/// An extracted string that remembers where it came from: the source string and the
+/// [`Ranges`] it was extracted from. Derefs to `str`.
+///
+/// Construct one with
+/// [`Strloin::from_ranges_provenance`](crate::Strloin::from_ranges_provenance).
+///
+/// # Examples
+///
+/// ```
+/// use strloin::Strloin;
+///
+/// let strloin = Strloin::new("key = value");
+/// let got = strloin.from_ranges_provenance([0..3, 6..11].into_iter().collect());
+///
+/// assert_eq!(got, "keyvalue");
+/// assert!(!got.is_borrowed());
+/// assert_eq!(got.ranges().ranges, [0..3, 6..11]);
+/// assert_eq!(got.source(), "key = value");
+/// ```
+#[derive(Clone)]
+pub struct ProvenanceCow<'a> {
+    cow: Cow<'a, str>,
+    source: &'a str,
+    ranges: Ranges,
+}
+
+impl<'a> ProvenanceCow<'a> {
+    pub(crate) const fn new(cow: Cow<'a, str>, source: &'a str, ranges: Ranges) -> Self {
+        Self {
+            cow,
+            source,
+            ranges,
+        }
+    }
+
+    /// Returns `true` if the result borrows from the source string.
+    #[must_use]
+    pub const fn is_borrowed(&self) -> bool {
+        matches!(self.cow, Borrowed(_))
+    }
+
+    /// Returns the source string the result was extracted from.
+    #[must_use]
+    pub const fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the ranges the result was extracted from.
+    #[must_use]
+    pub const fn ranges(&self) -> &Ranges {
+        &self.ranges
+    }
+
+    /// Returns the extracted string as a [`Cow`].
+    #[must_use]
+    pub const fn as_cow(&self) -> &Cow<'a, str> {
+        &self.cow
+    }
+
+    /// Returns a [`Selection`] of the source string with the ranges the result came from.
+    #[must_use]
+    pub fn selection(&self) -> Selection<'a> {
+        Selection::new(self.source, self.ranges.clone())
+    }
+
+    /// Converts the result into its [`Cow`], discarding its provenance.
+    #[must_use]
+    pub fn into_cow(self) -> Cow<'a, str> {
+        self.cow
+    }
+
+    /// Converts the result into its [`Cow`], source string, and ranges.
+    #[must_use]
+    pub fn into_parts(self) -> (Cow<'a, str>, &'a str, Ranges) {
+        (self.cow, self.source, self.ranges)
+    }
+}
+
+impl Deref for ProvenanceCow<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.cow
+    }
+}
+
+impl AsRef<str> for ProvenanceCow<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for ProvenanceCow<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvenanceCow")
+            .field("cow", &self.cow)
+            .field("ranges", &self.ranges.ranges)
+            .finish()
+    }
+}
+
+impl fmt::Display for ProvenanceCow<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl PartialEq<str> for ProvenanceCow<'_> {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for ProvenanceCow<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use crate::{Borrowed, Ranges, Strloin};
+
+    #[test]
+    fn from_ranges_provenance() {
+        let strloin = Strloin::new("hello world");
+
+        let got = strloin.from_ranges_provenance([0..5, 5..11].into_iter().collect());
+        assert!(got.is_borrowed());
+        assert!(matches!(got.as_cow(), Borrowed("hello world")));
+        assert_eq!(got.ranges().ranges, [0..11]);
+        assert_eq!(got.selection(), "hello world");
+
+        let got = strloin.from_ranges_provenance([6..11, 0..5].into_iter().collect());
+        assert_eq!(got, "worldhello");
+        assert_eq!(got.len(), 10);
+        assert_eq!(got.to_string(), "worldhello");
+        assert_eq!(
+            format!("{got:?}"),
+            "ProvenanceCow { cow: \"worldhello\", ranges: [6..11, 0..5] }"
+        );
+
+        let (cow, source, ranges) = got.clone().into_parts();
+        assert_eq!((cow.as_ref(), source), ("worldhello", "hello world"));
+        assert_eq!(ranges.ranges, [6..11, 0..5]);
+        assert_eq!(got.into_cow(), "worldhello");
+
+        let got = strloin.from_ranges_provenance(Ranges::new());
+        assert!(matches!(got.into_cow(), Borrowed("")));
+    }
+}
@@ -0,0 +1,68 @@
+use std::ops::Range;
+
+/// Overflow-checked arithmetic on `Range<usize>`, for building ranges out of untrusted offsets
+/// and lengths without silently wrapping.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Range;
+/// use strloin::RangeExt;
+///
+/// assert_eq!(Range::checked_from_len(3, 4), Some(3..7));
+/// assert_eq!(Range::checked_from_len(3, usize::MAX), None);
+///
+/// let range = 3..7;
+/// assert_eq!(range.checked_shift(10), Some(13..17));
+/// assert_eq!(range.checked_grow(2), Some(3..9));
+/// assert_eq!(range.checked_grow(usize::MAX), None);
+/// ```
+pub trait RangeExt: Sized {
+    /// Returns the range of `len` bytes starting at `start`, or `None` if its end would overflow.
+    fn checked_from_len(start: usize, len: usize) -> Option<Self>;
+
+    /// Returns the range moved `offset` bytes later, or `None` if either end would overflow.
+    fn checked_shift(&self, offset: usize) -> Option<Self>;
+
+    /// Returns the range with its end moved `len` bytes later, or `None` if it would overflow.
+    fn checked_grow(&self, len: usize) -> Option<Self>;
+}
+
+impl RangeExt for Range<usize> {
+    fn checked_from_len(start: usize, len: usize) -> Option<Self> {
+        Some(start..start.checked_add(len)?)
+    }
+
+    fn checked_shift(&self, offset: usize) -> Option<Self> {
+        Some(self.start.checked_add(offset)?..self.end.checked_add(offset)?)
+    }
+
+    fn checked_grow(&self, len: usize) -> Option<Self> {
+        Some(self.start..self.end.checked_add(len)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked() {
+        assert_eq!(Range::checked_from_len(0, 0), Some(0..0));
+        assert_eq!(
+            Range::checked_from_len(usize::MAX, 0),
+            Some(usize::MAX..usize::MAX)
+        );
+        assert_eq!(Range::checked_from_len(usize::MAX, 1), None);
+
+        assert_eq!((2..4).checked_shift(0), Some(2..4));
+        assert_eq!(
+            (2..4).checked_shift(usize::MAX - 4),
+            Some(usize::MAX - 2..usize::MAX)
+        );
+        assert_eq!((2..4).checked_shift(usize::MAX - 3), None);
+
+        assert_eq!((2..4).checked_grow(usize::MAX - 4), Some(2..usize::MAX));
+        assert_eq!((2..4).checked_grow(usize::MAX - 3), None);
+    }
+}
@@ -1,9 +1,36 @@
-use std::ops::Range;
+use crate::error::Error;
+use crate::index::{widen, RangeIndex};
+use crate::policy::CollapsePolicy;
+use crate::strloin::Strloin;
+use std::fmt;
+use std::ops::{Index, Range};
+use std::slice::Iter;
+use std::str::FromStr;
+use std::vec::IntoIter;
 
 /// A data structure for incrementally building a list of ranges.
+///
+/// Offsets are stored as `usize` by default. Any other [`RangeIndex`], such as `u32`, can be
+/// used instead to save memory when many ranges are kept alive; the methods that do arithmetic
+/// on offsets are only available for `usize`, so convert with [`Ranges::try_narrow`] and
+/// [`Ranges::widen`].
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Ranges, Strloin};
+///
+/// let mut ranges = Ranges::<u32>::default();
+/// ranges.push(0..5);
+/// ranges.push(5..11);
+/// assert_eq!(ranges.ranges, vec![0..11]);
+///
+/// let strloin = Strloin::new("hello world");
+/// assert_eq!(strloin.from_ranges_obj(&ranges), "hello world");
+/// ```
 #[derive(Debug, Default, Clone)]
-pub struct Ranges {
-    pub ranges: Vec<Range<usize>>,
+pub struct Ranges<I = usize> {
+    pub ranges: Vec<Range<I>>,
 }
 
 impl Ranges {
@@ -29,25 +56,429 @@ impl Ranges {
         Self { ranges }
     }
 
-    /// Adds a new range to the [`Ranges`], collapsing if possible.
+    /// Construct a new [`Ranges`] selecting every non-overlapping occurrence of `needle` in
+    /// `source`, as found by [`str::match_indices`]. Occurrences that abut are collapsed into one
+    /// range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let source = "one, two,, three";
+    /// let commas = Ranges::find_all(source, ",");
+    /// assert_eq!(commas.ranges, vec![3..4, 8..10]);
+    ///
+    /// let strloin = Strloin::new(source);
+    /// let without = commas.complement(source.len());
+    /// assert_eq!(strloin.from_ranges_obj(&without), "one two three");
+    /// ```
+    #[must_use]
+    pub fn find_all(source: &str, needle: &str) -> Self {
+        source
+            .match_indices(needle)
+            .map(|(start, m)| start..start + m.len())
+            .collect()
+    }
+
+    /// Construct a new [`Ranges`] with one range for each field of `source` separated by
+    /// `delimiter`, as found by [`str::split`]. Every field gets its own range, including empty
+    /// ones, so the ranges correspond one-to-one with the fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let source = "name,,age";
+    /// let fields = Ranges::from_split(source, ",");
+    /// assert_eq!(fields.ranges, vec![0..4, 5..5, 6..9]);
+    ///
+    /// let strloin = Strloin::new(source);
+    /// assert_eq!(strloin.from_ranges(&fields.ranges[2..]), "age");
+    /// ```
+    #[must_use]
+    pub fn from_split(source: &str, delimiter: &str) -> Self {
+        Self {
+            ranges: Strloin::new(source).split_ranges(delimiter).collect(),
+        }
+    }
+
+    /// Construct a new [`Ranges`] selecting each run of characters of `source` for which `f`
+    /// returns `true`. The ranges always fall on character boundaries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let source = "héllo, wörld!";
+    /// let words = Ranges::from_char_filter(source, char::is_alphanumeric);
+    /// assert_eq!(words.ranges, vec![0..6, 8..14]);
+    /// assert_eq!(Strloin::new(source).from_ranges_obj(&words), "héllowörld");
+    /// ```
+    #[must_use]
+    pub fn from_char_filter<F: FnMut(char) -> bool>(source: &str, mut f: F) -> Self {
+        let mut ranges = Self::new();
+        for (i, c) in source.char_indices() {
+            if f(c) {
+                ranges.push(i..i + c.len_utf8());
+            }
+        }
+        ranges
+    }
+
+    /// Construct a new [`Ranges`] selecting each run of bytes whose entry in `mask` is `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mask = [false, true, true, false, true];
+    /// assert_eq!(Ranges::from_mask(&mask).ranges, vec![1..3, 4..5]);
+    /// ```
+    #[must_use]
+    pub fn from_mask(mask: &[bool]) -> Self {
+        let mut ranges = Self::new();
+        let mut start = None;
+
+        for (i, &selected) in mask.iter().enumerate() {
+            match (selected, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    ranges.push(s..i);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            ranges.push(s..mask.len());
+        }
+
+        ranges
+    }
+
+    /// Construct a new [`Ranges`] selecting each run of set bits among the first `len` bits of
+    /// `words`, where bit `i` is the bit `1 << (i % 64)` of `words[i / 64]`. Bits past the end of
+    /// `words` are treated as unset.
     ///
     /// # Examples
     ///
     /// ```
     /// use strloin::Ranges;
     ///
+    /// let bits = [0b1011_0110, u64::MAX];
+    /// assert_eq!(Ranges::from_bits(&bits, 70).ranges, vec![1..3, 4..6, 7..8, 64..70]);
+    /// ```
+    #[must_use]
+    pub fn from_bits(words: &[u64], len: usize) -> Self {
+        let mut ranges = Self::new();
+        let mut pos = 0;
+
+        while pos < len {
+            let word = words.get(pos / 64).map_or(0, |w| w >> (pos % 64));
+            if word == 0 {
+                pos = (pos / 64 + 1) * 64;
+                continue;
+            }
+            pos += word.trailing_zeros() as usize;
+            if pos >= len {
+                break;
+            }
+
+            let start = pos;
+            loop {
+                let unset = words.get(pos / 64).map_or(u64::MAX, |w| !w >> (pos % 64));
+                if unset == 0 {
+                    pos = (pos / 64 + 1) * 64;
+                } else {
+                    pos += unset.trailing_zeros() as usize;
+                    break;
+                }
+            }
+
+            ranges.push(start..pos.min(len));
+        }
+
+        ranges
+    }
+
+    /// Converts the ranges to a narrower [`RangeIndex`], such as `u32`, to save memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Overflow`] for the first range with an offset that doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let ranges: Ranges = [0..5, 6..11].into_iter().collect();
+    /// let narrow = ranges.try_narrow::<u8>().unwrap();
+    /// assert_eq!(narrow.ranges, vec![0..5, 6..11]);
+    ///
+    /// let ranges: Ranges = [0..5, 6..300].into_iter().collect();
+    /// assert_eq!(
+    ///     ranges.try_narrow::<u8>().unwrap_err(),
+    ///     Error::Overflow { index: 1, range: 6..300 },
+    /// );
+    /// ```
+    pub fn try_narrow<I: RangeIndex>(&self) -> Result<Ranges<I>, Error> {
+        let ranges = self
+            .ranges
+            .iter()
+            .enumerate()
+            .map(|(index, range)| {
+                let overflow = || Error::Overflow {
+                    index,
+                    range: range.clone(),
+                };
+                let start = I::from_usize(range.start).ok_or_else(overflow)?;
+                let end = I::from_usize(range.end).ok_or_else(overflow)?;
+                Ok(start..end)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Ranges { ranges })
+    }
+
+    /// Adds a new range to the [`Ranges`], collapsing if possible, or returns an error if the range
+    /// is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if the range starts after it ends, in which case it is not
+    /// added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
     /// let mut ranges = Ranges::from(0..5);
-    /// ranges.push(5..11);
+    /// assert_eq!(ranges.try_push(5..11), Ok(()));
     /// assert_eq!(ranges.ranges, vec![0..11]);
     ///
-    /// ranges.push(4..8);
-    /// assert_eq!(ranges.ranges, vec![0..11, 4..8]);
+    /// assert_eq!(ranges.try_push(8..4), Err(Error::Inverted { index: 1, range: 8..4 }));
+    /// assert_eq!(ranges.ranges, vec![0..11]);
+    /// ```
+    pub fn try_push(&mut self, range: Range<usize>) -> Result<(), Error> {
+        if range.start > range.end {
+            return Err(Error::Inverted {
+                index: self.ranges.len(),
+                range,
+            });
+        }
+
+        self.push(range);
+        Ok(())
+    }
+
+    /// Adds a new range to the [`Ranges`], collapsing as the given [`CollapsePolicy`] allows, or
+    /// returns an error if the range is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if the range starts after it ends, in which case it is not
+    /// added.
+    ///
+    /// # Panics
+    ///
+    /// Panics instead of returning an error if the policy's `panic_on_invalid` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{CollapsePolicy, Ranges};
+    ///
+    /// let policy = CollapsePolicy {
+    ///     merge_overlapping: true,
+    ///     ..CollapsePolicy::new()
+    /// };
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// assert_eq!(ranges.try_push_with(3..8, &policy), Ok(()));
+    /// assert_eq!(ranges.ranges, vec![0..8]);
+    /// ```
+    pub fn try_push_with(
+        &mut self,
+        range: Range<usize>,
+        policy: &CollapsePolicy,
+    ) -> Result<(), Error> {
+        policy.validate(self.ranges.len(), &range)?;
+
+        if let Some(last) = self.ranges.last_mut() {
+            if policy.merge(last, &range) {
+                return Ok(());
+            }
+        }
+
+        self.ranges.push(range);
+        Ok(())
+    }
+
+    /// Adds a new range to the [`Ranges`], collapsing if possible, or returns an error if the range
+    /// is invalid or starts before the end of the last range. Pushing only with `try_push_ordered`
+    /// guarantees that the ranges are sorted and disjoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Inverted`] if the range starts after it ends, or [`Error::OutOfOrder`] if
+    /// it starts before the end of the last range. In either case the range is not added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// assert_eq!(ranges.try_push_ordered(6..11), Ok(()));
+    ///
+    /// assert_eq!(
+    ///     ranges.try_push_ordered(8..12),
+    ///     Err(Error::OutOfOrder { index: 2, range: 8..12, previous: 6..11 }),
+    /// );
+    /// assert_eq!(ranges.ranges, vec![0..5, 6..11]);
+    /// ```
+    pub fn try_push_ordered(&mut self, range: Range<usize>) -> Result<(), Error> {
+        if let Some(last) = self.ranges.last() {
+            if range.start < last.end {
+                return Err(Error::OutOfOrder {
+                    index: self.ranges.len(),
+                    range,
+                    previous: last.clone(),
+                });
+            }
+        }
+
+        self.try_push(range)
+    }
+
+    /// Adds a new range of `len` bytes starting at `start`, collapsing if possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start + len` overflows `usize`, in release builds too. See
+    /// [`Ranges::try_push_len`] for a non-panicking version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::new();
+    /// ranges.push_len(0, 5);
+    /// ranges.push_len(5, 6);
+    /// assert_eq!(ranges.ranges, vec![0..11]);
+    /// ```
+    pub fn push_len(&mut self, start: usize, len: usize) {
+        if let Err(e) = self.try_push_len(start, len) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Adds a new range of `len` bytes starting at `start`, collapsing if possible.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LengthOverflow`] if `start + len` overflows `usize`, in which case no range
+    /// is added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let mut ranges = Ranges::new();
+    /// assert_eq!(ranges.try_push_len(3, 2), Ok(()));
+    /// assert_eq!(
+    ///     ranges.try_push_len(5, usize::MAX),
+    ///     Err(Error::LengthOverflow { index: 1, start: 5, len: usize::MAX }),
+    /// );
+    /// assert_eq!(ranges.ranges, vec![3..5]);
+    /// ```
+    pub fn try_push_len(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        let end = start.checked_add(len).ok_or(Error::LengthOverflow {
+            index: self.ranges.len(),
+            start,
+            len,
+        })?;
+
+        self.push(start..end);
+        Ok(())
+    }
+
+    /// Adds a new range of `len` bytes starting at `start`, collapsing if possible, where both
+    /// come from untrusted fields of any integer type. Unlike casting with `as`, a value that
+    /// doesn't fit in `usize` (such as a `u64` on a 32-bit target) is rejected rather than
+    /// truncated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OffsetOverflow`], naming the field and its value, if `start` or `len`
+    /// doesn't fit in `usize`, or
+    /// [`Error::LengthOverflow`] if `start + len` overflows `usize`, in which case no range is
+    /// added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let mut ranges = Ranges::new();
+    /// assert_eq!(ranges.push_range_checked(3_u64, 2), Ok(()));
+    /// assert_eq!(
+    ///     ranges.push_range_checked(-1_i64, 2),
+    ///     Err(Error::OffsetOverflow { index: 1, field: "start", value: "-1".to_owned() }),
+    /// );
+    /// assert_eq!(ranges.ranges, vec![3..5]);
+    /// ```
+    pub fn push_range_checked<T>(&mut self, start: T, len: T) -> Result<(), Error>
+    where
+        T: TryInto<usize> + Copy + fmt::Display,
+    {
+        let index = self.ranges.len();
+        let overflow = |field, value: T| Error::OffsetOverflow {
+            index,
+            field,
+            value: value.to_string(),
+        };
+        let start_usize = start.try_into().map_err(|_| overflow("start", start))?;
+        let len_usize = len.try_into().map_err(|_| overflow("len", len))?;
+        self.try_push_len(start_usize, len_usize)
+    }
+
+    /// Adds a new range to the [`Ranges`], merging it into the last range if they overlap or
+    /// abut, so overlapping spans select their bytes only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// ranges.push_merge(3..8);
+    /// assert_eq!(ranges.ranges, vec![0..8]);
+    ///
+    /// ranges.push_merge(2..4);
+    /// assert_eq!(ranges.ranges, vec![0..8]);
+    ///
+    /// ranges.push_merge(9..11);
+    /// assert_eq!(ranges.ranges, vec![0..8, 9..11]);
     /// ```
-    pub fn push(&mut self, range: Range<usize>) {
+    pub fn push_merge(&mut self, range: Range<usize>) {
         if let Some(last) = self.ranges.last_mut() {
             #[allow(clippy::suspicious_operation_groupings)]
-            if range.start == last.end && last.start < last.end && range.start < range.end {
-                last.end = range.end;
+            if last.start < last.end
+                && range.start < range.end
+                && range.start <= last.end
+                && last.start <= range.end
+            {
+                last.start = last.start.min(range.start);
+                last.end = last.end.max(range.end);
                 return;
             }
         }
@@ -55,17 +486,25 @@ impl Ranges {
         self.ranges.push(range);
     }
 
-    /// Adds a new range to the [`Ranges`], collapsing if possible.
+    /// Adds a new range to the [`Ranges`], collapsing if possible. Ranges collapse exactly as
+    /// with [`Ranges::push`]: an empty range is dropped, and an empty last range is replaced.
     ///
     /// # Safety
     ///
     /// `push_unchecked` does not check whether the new range has `start` <= `end`. The caller must guarantee that the provided range is valid, otherwise it may
     /// produce bogus results. (Note that it is perfectly acceptable to push a
     /// range overlaps or precedes what's already in the `Ranges`)
+    ///
+    /// Debug builds check this with `debug_assert!`, so fuzzing and tests catch an invalid range
+    /// where it's pushed.
     pub unsafe fn push_unchecked(&mut self, range: Range<usize>) {
+        debug_assert!(
+            range.start <= range.end,
+            "push_unchecked given inverted range {range:?}"
+        );
+
         if let Some(last) = self.ranges.last_mut() {
-            if range.start == last.end {
-                last.end = range.end;
+            if merge_into(last, &range) {
                 return;
             }
         }
@@ -73,132 +512,2375 @@ impl Ranges {
         self.ranges.push(range);
     }
 
-    /// Removes all elements from the [`Ranges`].
-    pub fn clear(&mut self) {
-        self.ranges.clear();
+    /// Returns the total number of bytes selected by all ranges, which is the length of the
+    /// string [`Strloin::from_ranges_obj`] would produce. Overlapping ranges are counted each
+    /// time they occur.
+    ///
+    /// [`Strloin::from_ranges_obj`]: crate::Strloin::from_ranges_obj
+    #[must_use]
+    pub fn total_len(&self) -> usize {
+        self.ranges.iter().map(ExactSizeIterator::len).sum()
     }
-}
 
-impl From<Range<usize>> for Ranges {
-    fn from(range: Range<usize>) -> Self {
-        Self {
-            ranges: vec![range],
-        }
+    /// Returns the number of distinct source bytes selected by the ranges. Unlike
+    /// [`Ranges::total_len`], bytes selected by more than one range are counted once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [0..5, 3..8, 10..12].into_iter().collect();
+    /// assert_eq!(ranges.total_len(), 12);
+    /// assert_eq!(ranges.covered_len(), 10);
+    /// ```
+    #[must_use]
+    pub fn covered_len(&self) -> usize {
+        self.normalized().total_len()
     }
-}
 
-impl FromIterator<Range<usize>> for Ranges {
-    fn from_iter<I: IntoIterator<Item = Range<usize>>>(iter: I) -> Self {
-        let iter = iter.into_iter();
-        let (capacity, _) = iter.size_hint();
-        let mut ranges = Self::with_capacity(capacity);
+    /// Returns the number of discontiguous runs of bytes that the ranges select, in order, which
+    /// measures how fragmented the selection is. Empty ranges select nothing, so they aren't
+    /// counted, and a range that starts where the previous one ended continues its run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges = Ranges {
+    ///     ranges: vec![0..5, 5..5, 5..8, 10..12, 0..2],
+    /// };
+    /// assert_eq!(ranges.len(), 5);
+    /// assert_eq!(ranges.run_count(), 3);
+    /// ```
+    #[must_use]
+    pub fn run_count(&self) -> usize {
+        let mut end = None;
+        let mut count = 0;
 
-        for range in iter {
-            ranges.push(range);
+        for range in self.ranges.iter().filter(|r| r.start < r.end) {
+            if end != Some(range.start) {
+                count += 1;
+            }
+            end = Some(range.end);
         }
 
-        ranges
+        count
     }
-}
 
-impl Extend<Range<usize>> for Ranges {
-    fn extend<T: IntoIterator<Item = Range<usize>>>(&mut self, iter: T) {
-        for range in iter {
-            self.push(range);
-        }
+    /// Returns the ranges as `[start, end]` pairs. This is the stable representation for
+    /// persisting a [`Ranges`], for example with serde, and [`Ranges::from_pairs`] reads it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [0..5, 6..11].into_iter().collect();
+    /// let pairs = ranges.to_pairs();
+    /// assert_eq!(pairs, vec![[0, 5], [6, 11]]);
+    /// assert_eq!(Ranges::from_pairs(&pairs).ranges, ranges.ranges);
+    /// ```
+    #[must_use]
+    pub fn to_pairs(&self) -> Vec<[usize; 2]> {
+        self.ranges.iter().map(|r| [r.start, r.end]).collect()
     }
-}
-
-/// Collapse a slice of ranges into a single contiguous range, if possible.
-///
-/// # Examples
-///
-/// ```
-/// use strloin::collapse_ranges;
-///
-/// assert_eq!(collapse_ranges(&[0..5]), Some(0..5));
-/// assert_eq!(collapse_ranges(&[0..5, 5..11]), Some(0..11));
-/// assert_eq!(collapse_ranges(&[0..5, 6..11]), None);
-/// ```
-#[must_use]
-#[allow(clippy::module_name_repetitions)]
-pub fn collapse_ranges(ranges: &[Range<usize>]) -> Option<Range<usize>> {
-    let mut rs = ranges.iter();
-    let Some(first) = rs.next() else {
-        return Some(Range { start: 0, end: 0 });
-    };
-    let start = first.start;
-    let mut end = first.end;
 
-    if start > end {
-        return None;
+    /// Construct a new [`Ranges`] from `[start, end]` pairs, as produced by [`Ranges::to_pairs`],
+    /// collapsing where possible.
+    #[must_use]
+    pub fn from_pairs(pairs: &[[usize; 2]]) -> Self {
+        pairs.iter().map(|&[start, end]| start..end).collect()
     }
 
-    for r in rs {
-        if r.start != end || r.end < r.start {
-            return None;
+    /// Encodes the ranges compactly, for persisting large numbers of them. Ranges that are close
+    /// together and short take only a few bytes each, and [`Ranges::from_bytes`] decodes them.
+    ///
+    /// The encoding is a sequence of LEB128 varints: the number of ranges, then for each range
+    /// the zigzag-encoded distance from the end of the previous range (or from 0) to its start,
+    /// followed by its length. Arithmetic wraps, so every [`Ranges`] round-trips exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [1000..1005, 1006..1011].into_iter().collect();
+    /// let bytes = ranges.to_bytes();
+    /// assert_eq!(bytes, [2, 0xd0, 0x0f, 5, 2, 5]);
+    /// assert_eq!(Ranges::from_bytes(&bytes).unwrap().ranges, ranges.ranges);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.ranges.len() * 2);
+        write_varint(&mut bytes, self.ranges.len() as u64);
+
+        let mut end = 0_usize;
+        for range in &self.ranges {
+            let delta = range.start.wrapping_sub(end);
+            let zigzag = (delta << 1) ^ 0_usize.wrapping_sub(delta >> (usize::BITS - 1));
+            write_varint(&mut bytes, zigzag as u64);
+            write_varint(&mut bytes, range.end.wrapping_sub(range.start) as u64);
+            end = range.end;
         }
-        end = r.end;
+
+        bytes
     }
 
-    Some(Range { start, end })
-}
+    /// Decodes ranges encoded by [`Ranges::to_bytes`]. The ranges come back exactly as they were
+    /// encoded, without collapsing abutting or empty ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Decode`] if `bytes` is truncated, has trailing bytes, or encodes a value
+    /// that doesn't fit in a `usize`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut offset = 0;
+        let count = read_varint(bytes, &mut offset)?;
+        let mut ranges = Self::with_capacity(count.min(bytes.len()));
+
+        let mut end = 0_usize;
+        for _ in 0..count {
+            let zigzag = read_varint(bytes, &mut offset)?;
+            let len = read_varint(bytes, &mut offset)?;
+            let delta = (zigzag >> 1) ^ 0_usize.wrapping_sub(zigzag & 1);
+            let start = end.wrapping_add(delta);
+            end = start.wrapping_add(len);
+            ranges.ranges.push(start..end);
+        }
+
+        if offset < bytes.len() {
+            return Err(Error::Decode { offset });
+        }
+
+        Ok(ranges)
+    }
+
+    /// Translates every range by the given offset, for example to rebase ranges of a
+    /// sub-buffer onto the larger document it was carved from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range would overflow or underflow `usize`, in which case the ranges are left
+    /// unchanged. See [`Ranges::try_shift_by`] for a non-panicking version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges: Ranges = [0..5, 6..11].into_iter().collect();
+    /// ranges.shift_by(100);
+    /// assert_eq!(ranges.ranges, vec![100..105, 106..111]);
+    ///
+    /// ranges.shift_by(-100);
+    /// assert_eq!(ranges.ranges, vec![0..5, 6..11]);
+    /// ```
+    pub fn shift_by(&mut self, offset: isize) {
+        if let Err(e) = self.try_shift_by(offset) {
+            panic!("cannot shift by {offset}: {e}");
+        }
+    }
+
+    /// Translates every range by the given offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Overflow`] for the first range that would overflow or underflow `usize`,
+    /// in which case the ranges are left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let mut ranges: Ranges = [3..5, 6..11].into_iter().collect();
+    /// assert_eq!(ranges.try_shift_by(-3), Ok(()));
+    /// assert_eq!(ranges.ranges, vec![0..2, 3..8]);
+    ///
+    /// assert_eq!(
+    ///     ranges.try_shift_by(-1),
+    ///     Err(Error::Overflow { index: 0, range: 0..2 }),
+    /// );
+    /// assert_eq!(ranges.ranges, vec![0..2, 3..8]);
+    /// ```
+    pub fn try_shift_by(&mut self, offset: isize) -> Result<(), Error> {
+        let magnitude = offset.unsigned_abs();
+        let shift = |n: usize| {
+            if offset < 0 {
+                n.checked_sub(magnitude)
+            } else {
+                n.checked_add(magnitude)
+            }
+        };
+
+        for (index, range) in self.ranges.iter().enumerate() {
+            if shift(range.start).is_none() || shift(range.end).is_none() {
+                return Err(Error::Overflow {
+                    index,
+                    range: range.clone(),
+                });
+            }
+        }
+
+        for range in &mut self.ranges {
+            range.start = shift(range.start).unwrap_or(range.start);
+            range.end = shift(range.end).unwrap_or(range.end);
+        }
+
+        Ok(())
+    }
+
+    /// Restricts the ranges to a source of the given length: ranges that extend past `len` are
+    /// truncated to end there, and ranges that lie entirely past it are dropped. Returns how many
+    /// ranges were truncated or dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges: Ranges = [0..2, 8..10, 2..4, 4..7].into_iter().collect();
+    /// assert_eq!(ranges.clamp_to(5), 2);
+    /// assert_eq!(ranges.ranges, vec![0..5]);
+    /// ```
+    pub fn clamp_to(&mut self, len: usize) -> usize {
+        let mut modified = 0;
+        let ranges = std::mem::take(&mut self.ranges);
+
+        for mut range in ranges {
+            if range.end > len {
+                modified += 1;
+                if range.start >= len {
+                    continue;
+                }
+                range.end = len;
+            }
+            self.push(range);
+        }
+
+        modified
+    }
+
+    /// Sorts the ranges by start and merges any that overlap or abut, leaving the minimal list of
+    /// disjoint ranges covering the same bytes. Empty (and inverted) ranges cover nothing, so they
+    /// are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges: Ranges = [6..9, 0..2, 8..11, 1..3, 4..4].into_iter().collect();
+    /// ranges.normalize();
+    /// assert_eq!(ranges.ranges, vec![0..3, 6..11]);
+    /// ```
+    pub fn normalize(&mut self) {
+        self.ranges.retain(|r| r.start < r.end);
+        self.ranges.sort_unstable_by_key(|r| r.start);
+        self.ranges.dedup_by(|next, last| {
+            if next.start > last.end {
+                return false;
+            }
+            last.end = last.end.max(next.end);
+            true
+        });
+    }
+
+    /// Returns `true` if the byte at `index` is covered by one of the ranges. This is a binary
+    /// search, so the ranges must be sorted and disjoint, as they are after
+    /// [`Ranges::normalize`]; otherwise the result is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [2..4, 6..9].into_iter().collect();
+    /// assert!(ranges.contains(3));
+    /// assert!(!ranges.contains(4));
+    /// assert!(ranges.contains(6));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, index: usize) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= index);
+        self.ranges.get(i).map_or(false, |r| r.start <= index)
+    }
+
+    /// Returns `true` if any byte of `range` is covered by one of the ranges. Like
+    /// [`Ranges::contains`], the ranges must be sorted and disjoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [2..4, 6..9].into_iter().collect();
+    /// assert!(ranges.overlaps(3..7));
+    /// assert!(!ranges.overlaps(4..6));
+    /// ```
+    #[must_use]
+    pub fn overlaps(&self, range: Range<usize>) -> bool {
+        if range.start >= range.end {
+            return false;
+        }
+
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges[i..]
+            .iter()
+            .take_while(|r| r.start < range.end)
+            .any(|r| r.start < r.end)
+    }
+
+    /// Returns `true` if every byte of `range` is covered by the ranges, which is always the case
+    /// for an empty `range`. Like [`Ranges::contains`], the ranges must be sorted and disjoint, but
+    /// `range` may span several abutting ranges, such as `0..2` and `2..4`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [2..4, 6..9].into_iter().collect();
+    /// assert!(ranges.contains_range(6..9));
+    /// assert!(!ranges.contains_range(3..7));
+    ///
+    /// let ranges = Ranges { ranges: vec![0..2, 2..4] };
+    /// assert!(ranges.contains_range(1..3));
+    /// ```
+    #[must_use]
+    pub fn contains_range(&self, range: Range<usize>) -> bool {
+        if range.start >= range.end {
+            return true;
+        }
+
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        let mut covered = range.start;
+        for r in &self.ranges[i..] {
+            if r.start > covered {
+                break;
+            }
+            covered = covered.max(r.end);
+            if covered >= range.end {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Splits the ranges at the source offset `pos`, returning the parts before `pos` and the parts
+    /// from `pos` onwards, each in their original order. A range straddling `pos` is split in two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [8..10, 0..6].into_iter().collect();
+    /// let (left, right) = ranges.split_at(4);
+    /// assert_eq!(left.ranges, vec![0..4]);
+    /// assert_eq!(right.ranges, vec![8..10, 4..6]);
+    /// ```
+    #[must_use]
+    pub fn split_at(&self, pos: usize) -> (Self, Self) {
+        let mut left = Self::new();
+        let mut right = Self::new();
+
+        for range in &self.ranges {
+            if range.start >= pos {
+                right.push(range.clone());
+            } else if range.end <= pos {
+                left.push(range.clone());
+            } else {
+                left.push(range.start..pos);
+                right.push(pos..range.end);
+            }
+        }
+
+        (left, right)
+    }
+
+    /// Returns an iterator over the regions of a source of length `len` that aren't covered by any
+    /// range, in order. The ranges are assumed to be sorted by start, as they are after
+    /// [`Ranges::normalize`]. Empty ranges cover nothing, so they're skipped. See
+    /// [`Ranges::complement`] for ranges in any order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [2..4, 6..7].into_iter().collect();
+    /// let gaps: Vec<_> = ranges.gaps(10).collect();
+    /// assert_eq!(gaps, vec![0..2, 4..6, 7..10]);
+    /// ```
+    #[must_use]
+    pub fn gaps(&self, len: usize) -> Gaps<'_> {
+        Gaps {
+            ranges: self.ranges.iter(),
+            cursor: 0,
+            len,
+        }
+    }
+
+    /// Returns the ranges covering exactly the bytes of `0..len` that this [`Ranges`] doesn't, in
+    /// order. The ranges may be in any order and may overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello, world");
+    /// let ranges: Ranges = [7..12, 4..6].into_iter().collect();
+    ///
+    /// let complement = ranges.complement(strloin.source.len());
+    /// assert_eq!(complement.ranges, vec![0..4, 6..7]);
+    /// assert_eq!(strloin.from_ranges_obj(&complement), "hell ");
+    /// ```
+    #[must_use]
+    pub fn complement(&self, len: usize) -> Self {
+        self.normalized().gaps(len).collect()
+    }
+
+    /// Returns the normalized ranges covering every byte covered by either this [`Ranges`] or
+    /// `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let a: Ranges = [0..3, 8..10].into_iter().collect();
+    /// let b: Ranges = [2..5, 6..8].into_iter().collect();
+    /// assert_eq!(a.union(&b).ranges, vec![0..5, 6..10]);
+    /// ```
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = Self::with_capacity(self.len() + other.len());
+        ranges.ranges.extend_from_slice(&self.ranges);
+        ranges.ranges.extend_from_slice(&other.ranges);
+        ranges.normalize();
+        ranges
+    }
+
+    /// Returns the normalized ranges covering every byte covered by both this [`Ranges`] and
+    /// `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let matches: Ranges = [2..6, 9..14, 20..25].into_iter().collect();
+    /// let viewport = Ranges::from(4..22);
+    /// assert_eq!(matches.intersection(&viewport).ranges, vec![4..6, 9..14, 20..22]);
+    /// ```
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (a, b) = (self.normalized(), other.normalized());
+        let (mut i, mut j) = (0, 0);
+        let mut ranges = Self::new();
+
+        while let (Some(x), Some(y)) = (a.ranges.get(i), b.ranges.get(j)) {
+            let start = x.start.max(y.start);
+            let end = x.end.min(y.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            if x.end < y.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        ranges
+    }
+
+    /// Returns the normalized ranges covering every byte covered by this [`Ranges`] but not by
+    /// `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let strloin = Strloin::new("say \"hi\" now");
+    /// let quoted = Ranges::from(0..12);
+    /// let quotes: Ranges = [4..5, 7..8].into_iter().collect();
+    ///
+    /// let stripped = quoted.difference(&quotes);
+    /// assert_eq!(stripped.ranges, vec![0..4, 5..7, 8..12]);
+    /// assert_eq!(strloin.from_ranges_obj(&stripped), "say hi now");
+    /// ```
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let (a, b) = (self.normalized(), other.normalized());
+        let mut ys = b.ranges.iter().peekable();
+        let mut ranges = Self::new();
+
+        for x in &a {
+            let mut cursor = x.start;
+
+            while let Some(y) = ys.peek() {
+                if y.start >= x.end {
+                    break;
+                }
+                if y.start > cursor {
+                    ranges.push(cursor..y.start);
+                }
+                cursor = cursor.max(y.end);
+                if y.end > x.end {
+                    break;
+                }
+                ys.next();
+            }
+
+            if cursor < x.end {
+                ranges.push(cursor..x.end);
+            }
+        }
+
+        ranges
+    }
+
+    /// Returns the normalized ranges covering every byte covered by exactly one of this
+    /// [`Ranges`] and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let before: Ranges = [0..4, 8..10].into_iter().collect();
+    /// let after: Ranges = [2..4, 8..12].into_iter().collect();
+    /// assert_eq!(before.symmetric_difference(&after).ranges, vec![0..2, 10..12]);
+    /// ```
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.union(other).difference(&self.intersection(other))
+    }
+
+    /// Returns a sorted copy with overlapping and abutting ranges merged, as with
+    /// [`Ranges::normalize`].
+    fn normalized(&self) -> Self {
+        let mut ranges = self.clone();
+        ranges.normalize();
+        ranges
+    }
+
+    /// Records the current state of the [`Ranges`], so that any pushes made afterwards can be
+    /// undone with [`Ranges::rollback`], including pushes that were collapsed into an existing
+    /// range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// let checkpoint = ranges.checkpoint();
+    ///
+    /// ranges.push(5..8);
+    /// ranges.push(9..11);
+    /// assert_eq!(ranges.ranges, vec![0..8, 9..11]);
+    ///
+    /// ranges.rollback(checkpoint);
+    /// assert_eq!(ranges.ranges, vec![0..5]);
+    /// ```
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        let last = self.ranges.last().map_or(0..0, Clone::clone);
+        Checkpoint {
+            len: self.ranges.len(),
+            start: last.start,
+            end: last.end,
+        }
+    }
+
+    /// Restores the [`Ranges`] to the state recorded by [`Ranges::checkpoint`], undoing any pushes
+    /// made since then. The result is unspecified if the ranges were modified other than by
+    /// pushing (for example with [`Ranges::pop`] or [`Ranges::clear`]) since the checkpoint.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.ranges.truncate(checkpoint.len);
+        if self.ranges.len() == checkpoint.len {
+            if let Some(last) = self.ranges.last_mut() {
+                *last = checkpoint.start..checkpoint.end;
+            }
+        }
+    }
+}
+
+impl<I: RangeIndex> Ranges<I> {
+    /// Adds a new range to the [`Ranges`], collapsing if possible. Empty ranges select nothing, so
+    /// an empty range is dropped, and an empty last range is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// ranges.push(5..11);
+    /// assert_eq!(ranges.ranges, vec![0..11]);
+    ///
+    /// ranges.push(4..8);
+    /// assert_eq!(ranges.ranges, vec![0..11, 4..8]);
+    /// ```
+    pub fn push(&mut self, range: Range<I>) {
+        if let Some(last) = self.ranges.last_mut() {
+            if merge_into(last, &range) {
+                return;
+            }
+        }
+
+        self.ranges.push(range);
+    }
+
+    /// Appends every range in `ranges`, collapsing just as pushing each in turn would, but
+    /// reserving space once and tracking the last range locally rather than re-reading the list
+    /// after every push.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// ranges.extend_from_slice(&[5..8, 9..10, 10..11]);
+    /// assert_eq!(ranges.ranges, vec![0..8, 9..11]);
+    /// ```
+    pub fn extend_from_slice(&mut self, ranges: &[Range<I>]) {
+        self.ranges.reserve(ranges.len());
+
+        let mut iter = ranges.iter().cloned();
+        let Some(mut current) = self.ranges.pop().or_else(|| iter.next()) else {
+            return;
+        };
+
+        for range in iter {
+            if !merge_into(&mut current, &range) {
+                self.ranges.push(std::mem::replace(&mut current, range));
+            }
+        }
+
+        self.ranges.push(current);
+    }
+
+    /// Returns the number of ranges stored, after collapsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [0..5, 5..8, 9..11].into_iter().collect();
+    /// assert_eq!(ranges.len(), 2);
+    /// assert_eq!(ranges.total_len(), 10);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns `true` if no ranges are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns `true` if the ranges select a single contiguous region, which is exactly when
+    /// [`Strloin::from_ranges_obj`] borrows. Since pushes collapse contiguous ranges as they go,
+    /// this is just a check that at most one range is stored.
+    ///
+    /// [`Strloin::from_ranges_obj`]: crate::Strloin::from_ranges_obj
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// ranges.push(5..11);
+    /// assert!(ranges.is_contiguous());
+    /// assert_eq!(ranges.as_single_range(), Some(&(0..11)));
+    ///
+    /// ranges.push(12..15);
+    /// assert!(!ranges.is_contiguous());
+    /// assert_eq!(ranges.as_single_range(), None);
+    /// ```
+    #[must_use]
+    pub fn is_contiguous(&self) -> bool {
+        self.ranges.len() <= 1
+    }
+
+    /// Returns the only range, if exactly one range is stored.
+    #[must_use]
+    pub fn as_single_range(&self) -> Option<&Range<I>> {
+        match self.ranges.as_slice() {
+            [range] => Some(range),
+            _ => None,
+        }
+    }
+
+    /// Returns the first range, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [0..5, 5..8, 9..11].into_iter().collect();
+    /// assert_eq!(ranges.first(), Some(&(0..8)));
+    /// assert_eq!(ranges.last(), Some(&(9..11)));
+    /// assert_eq!(ranges.get(1), Some(&(9..11)));
+    /// assert_eq!(ranges.get(2), None);
+    /// ```
+    #[must_use]
+    pub fn first(&self) -> Option<&Range<I>> {
+        self.ranges.first()
+    }
+
+    /// Returns the last range, or `None` if it is empty.
+    #[must_use]
+    pub fn last(&self) -> Option<&Range<I>> {
+        self.ranges.last()
+    }
+
+    /// Returns the range at position `index`, after collapsing, or `None` if out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Range<I>> {
+        self.ranges.get(index)
+    }
+
+    /// Returns an iterator over the stored ranges, after collapsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let ranges: Ranges = [0..5, 5..8, 9..11].into_iter().collect();
+    /// let starts: Vec<_> = ranges.iter().map(|r| r.start).collect();
+    /// assert_eq!(starts, [0, 9]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, Range<I>> {
+        self.ranges.iter()
+    }
+
+    /// Removes all elements from the [`Ranges`].
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Removes the last range from the [`Ranges`] and returns it, or `None` if it is empty.
+    ///
+    /// Note that this is the last range as stored, after collapsing, so it may cover several
+    /// pushes. To undo pushes exactly, use [`Ranges::checkpoint`] and [`Ranges::rollback`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    /// ranges.push(6..8);
+    /// ranges.push(8..11);
+    ///
+    /// assert_eq!(ranges.pop(), Some(6..11));
+    /// assert_eq!(ranges.ranges, vec![0..5]);
+    /// ```
+    pub fn pop(&mut self) -> Option<Range<I>> {
+        self.ranges.pop()
+    }
+
+    /// Shortens the [`Ranges`], keeping the first `len` ranges as stored and dropping the rest.
+    /// Has no effect if `len` is greater than the number of stored ranges.
+    pub fn truncate(&mut self, len: usize) {
+        self.ranges.truncate(len);
+    }
+
+    /// Inserts a range at position `index`, shifting all ranges after it. The range is inserted
+    /// as-is, without collapsing into its neighbors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of stored ranges.
+    pub fn insert(&mut self, index: usize, range: Range<I>) {
+        self.ranges.insert(index, range);
+    }
+
+    /// Keeps only the ranges for which `f` returns `true`, in order. Ranges left adjacent by the
+    /// removal of whatever was between them are collapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges: Ranges = [0..2, 5..6, 2..4, 8..11].into_iter().collect();
+    /// ranges.retain(|r| r.len() > 1);
+    /// assert_eq!(ranges.ranges, vec![0..4, 8..11]);
+    /// ```
+    pub fn retain<F: FnMut(&Range<I>) -> bool>(&mut self, f: F) {
+        self.ranges.retain(f);
+        self.collapse();
+    }
+
+    /// Removes consecutive repeated ranges, so that replaying the same range twice in a row
+    /// doesn't select its text twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let mut ranges: Ranges = [0..5, 8..9, 8..9, 8..9, 0..5].into_iter().collect();
+    /// ranges.dedup();
+    /// assert_eq!(ranges.ranges, vec![0..5, 8..9, 0..5]);
+    /// ```
+    pub fn dedup(&mut self) {
+        self.ranges.dedup();
+        self.collapse();
+    }
+
+    /// Collapses each range into the one before it where possible, as pushing them in order
+    /// would.
+    fn collapse(&mut self) {
+        self.ranges.dedup_by(|next, last| merge_into(last, next));
+    }
+
+    /// Converts the ranges to `usize` offsets, which every [`Ranges`] method supports.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Ranges;
+    ///
+    /// let narrow: Ranges<u16> = [0..5, 6..11].into_iter().collect();
+    /// assert_eq!(narrow.widen().ranges, vec![0..5, 6..11]);
+    /// ```
+    #[must_use]
+    pub fn widen(&self) -> Ranges {
+        Ranges {
+            ranges: self.ranges.iter().map(widen).collect(),
+        }
+    }
+
+    /// Removes and returns the range at position `index`, shifting all ranges after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Range<I> {
+        self.ranges.remove(index)
+    }
+}
+
+impl From<Range<usize>> for Ranges {
+    fn from(range: Range<usize>) -> Self {
+        Self {
+            ranges: vec![range],
+        }
+    }
+}
+
+impl From<Vec<Range<usize>>> for Ranges {
+    /// Collapses the ranges in place, as if each had been pushed in order.
+    fn from(ranges: Vec<Range<usize>>) -> Self {
+        let mut ranges = Self { ranges };
+        ranges.collapse();
+        ranges
+    }
+}
+
+impl From<&[Range<usize>]> for Ranges {
+    /// Collapses the ranges, as if each had been pushed in order.
+    fn from(ranges: &[Range<usize>]) -> Self {
+        ranges.iter().cloned().collect()
+    }
+}
+
+impl FromIterator<(usize, usize)> for Ranges {
+    /// Collects `(start, end)` pairs, collapsing as if each had been pushed in order.
+    fn from_iter<T: IntoIterator<Item = (usize, usize)>>(iter: T) -> Self {
+        iter.into_iter().map(|(start, end)| start..end).collect()
+    }
+}
+
+impl<I: RangeIndex> FromIterator<Range<I>> for Ranges<I> {
+    fn from_iter<T: IntoIterator<Item = Range<I>>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let (capacity, _) = iter.size_hint();
+        let mut ranges = Self {
+            ranges: Vec::with_capacity(capacity),
+        };
+
+        for range in iter {
+            ranges.push(range);
+        }
+
+        ranges
+    }
+}
+
+impl<I> Index<usize> for Ranges<I> {
+    type Output = Range<I>;
+
+    /// Returns the range at position `index`, after collapsing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.ranges[index]
+    }
+}
+
+impl<I> IntoIterator for Ranges<I> {
+    type Item = Range<I>;
+    type IntoIter = IntoIter<Range<I>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter()
+    }
+}
+
+impl<'r, I> IntoIterator for &'r Ranges<I> {
+    type Item = &'r Range<I>;
+    type IntoIter = Iter<'r, Range<I>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+impl<I: RangeIndex> Extend<Range<I>> for Ranges<I> {
+    fn extend<T: IntoIterator<Item = Range<I>>>(&mut self, iter: T) {
+        for range in iter {
+            self.push(range);
+        }
+    }
+}
+
+/// Formats the ranges compactly as comma-separated `start..end` pairs, such as `0..5,6..11`,
+/// which [`Ranges::from_str`] parses back.
+impl<I: RangeIndex> fmt::Display for Ranges<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}..{}", range.start, range.end)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Ranges {
+    type Err = Error;
+
+    /// Parses comma-separated `start..end` pairs, such as `0..5,6..11`, optionally surrounded by
+    /// whitespace. The empty string parses as no ranges. The ranges are kept exactly as written,
+    /// without collapsing abutting or empty ones, so any [`Ranges`] without inverted ranges
+    /// round-trips through its [`Display`](fmt::Display) format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Syntax`] if a range isn't of the form `start..end`, and
+    /// [`Error::Inverted`] if a range starts after it ends. The `index` of the error counts the
+    /// ranges in `s`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Ranges};
+    ///
+    /// let ranges: Ranges = "0..5, 6..11".parse().unwrap();
+    /// assert_eq!(ranges.ranges, vec![0..5, 6..11]);
+    /// assert_eq!(ranges.to_string(), "0..5,6..11");
+    ///
+    /// assert_eq!(
+    ///     "0..5,6-11".parse::<Ranges>().unwrap_err(),
+    ///     Error::Syntax { index: 1, text: "6-11".to_owned() },
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ranges = Self::new();
+        if s.trim().is_empty() {
+            return Ok(ranges);
+        }
+
+        for (index, text) in s.split(',').enumerate() {
+            let text = text.trim();
+            let syntax = || Error::Syntax {
+                index,
+                text: text.to_owned(),
+            };
+
+            let (start, end) = text.split_once("..").ok_or_else(syntax)?;
+            let start = start.parse().map_err(|_| syntax())?;
+            let end = end.parse().map_err(|_| syntax())?;
+            if start > end {
+                return Err(Error::Inverted {
+                    index,
+                    range: start..end,
+                });
+            }
+
+            ranges.ranges.push(start..end);
+        }
+
+        Ok(ranges)
+    }
+}
+
+/// An iterator over the uncovered regions of a source, created by [`Ranges::gaps`].
+#[derive(Debug, Clone)]
+pub struct Gaps<'r> {
+    ranges: Iter<'r, Range<usize>>,
+    cursor: usize,
+    len: usize,
+}
+
+impl Iterator for Gaps<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cursor < self.len {
+            let Some(range) = self.ranges.next() else {
+                let gap = self.cursor..self.len;
+                self.cursor = self.len;
+                return Some(gap);
+            };
+            if range.start >= range.end {
+                continue;
+            }
+
+            let start = self.cursor;
+            let end = range.start.min(self.len);
+            self.cursor = self.cursor.max(range.end);
+            if start < end {
+                return Some(start..end);
+            }
+        }
+
+        None
+    }
+}
+
+/// The state of a [`Ranges`] recorded by [`Ranges::checkpoint`], for undoing pushes with
+/// [`Ranges::rollback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    len: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Merges `range` into `last` if pushing it doesn't need a range of its own, returning `true` if
+/// so: when `range` continues `last`, `last` is extended, and since empty ranges contribute
+/// nothing, an empty `range` is dropped and an empty `last` is replaced.
+// `pub(crate)` even though the module is private, to mark this as an internal helper.
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn merge_into<I: RangeIndex>(last: &mut Range<I>, range: &Range<I>) -> bool {
+    CollapsePolicy::new().merge(last, range)
+}
+
+/// Appends `value` to `bytes` as an LEB128 varint.
+#[allow(clippy::cast_possible_truncation)]
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads an LEB128 varint from `bytes` at `offset`, advancing past it.
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<usize, Error> {
+    let start = *offset;
+    let mut value: u64 = 0;
+
+    for shift in (0..64).step_by(7) {
+        let Some(&byte) = bytes.get(*offset) else {
+            return Err(Error::Decode { offset: *offset });
+        };
+        *offset += 1;
+
+        let bits = u64::from(byte & 0x7f);
+        if bits << shift >> shift != bits {
+            break;
+        }
+        value |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return usize::try_from(value).map_err(|_| Error::Decode { offset: start });
+        }
+    }
+
+    Err(Error::Decode { offset: start })
+}
+
+/// Collapse a slice of ranges into a single contiguous range, if possible. Empty ranges select
+/// nothing, so they don't break contiguity.
+///
+/// This is a `const fn`, so a table of ranges can be collapsed at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Range;
+/// use strloin::collapse_ranges;
+///
+/// assert_eq!(collapse_ranges(&[0..5]), Some(0..5));
+/// assert_eq!(collapse_ranges(&[0..5, 5..11]), Some(0..11));
+/// assert_eq!(collapse_ranges(&[0..5, 6..11]), None);
+/// assert_eq!(collapse_ranges(&[0..5, 7..7, 5..11]), Some(0..11));
+///
+/// const HEADER: Option<Range<usize>> = collapse_ranges(&[0..4, 4..8, 8..16]);
+/// assert_eq!(HEADER, Some(0..16));
+/// ```
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub const fn collapse_ranges(ranges: &[Range<usize>]) -> Option<Range<usize>> {
+    let Some(first) = ranges.first() else {
+        return Some(0..0);
+    };
+    let mut start = first.start;
+    let mut end = first.start;
+
+    let mut i = 0;
+    while i < ranges.len() {
+        let r = &ranges[i];
+        if r.start > r.end {
+            return None;
+        }
+        if r.start != r.end {
+            if start == end {
+                start = r.start;
+            } else if r.start != end {
+                return None;
+            }
+            end = r.end;
+        }
+        i += 1;
+    }
+
+    Some(start..end)
+}
+
+/// Collapse ranges, in the order they're yielded, into a single contiguous range, if possible,
+/// as with [`collapse_ranges`].
+///
+/// This consumes the ranges, so ranges coming straight from a scanner don't need to be collected
+/// into a slice first. It stops at the first discontiguity.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::collapse_ranges_iter;
+///
+/// let words = "hello world".split(' ').scan(0, |offset, word| {
+///     let range = *offset..*offset + word.len();
+///     *offset = range.end;
+///     Some(range)
+/// });
+/// assert_eq!(collapse_ranges_iter(words), Some(0..10));
+/// assert_eq!(collapse_ranges_iter(vec![0..5, 6..11]), None);
+/// ```
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn collapse_ranges_iter<T: IntoIterator<Item = Range<usize>>>(
+    ranges: T,
+) -> Option<Range<usize>> {
+    let mut collapsed: Option<Range<usize>> = None;
+
+    for r in ranges {
+        if r.start > r.end {
+            return None;
+        }
+        match &mut collapsed {
+            None => collapsed = Some(r),
+            Some(_) if r.start == r.end => {}
+            Some(c) if c.start == c.end => *c = r,
+            Some(c) if c.end == r.start => c.end = r.end,
+            Some(_) => return None,
+        }
+    }
+
+    Some(collapsed.unwrap_or(Range { start: 0, end: 0 }))
+}
+
+/// Merges each range that overlaps or abuts the one before it, returning the minimal list of
+/// ranges covering the same bytes, in order.
+///
+/// Unlike [`Ranges::normalize`], the ranges aren't sorted, so only neighbours merge. Empty (and
+/// inverted) ranges cover nothing, so they are removed. Unlike [`collapse_ranges`], a
+/// discontiguity doesn't lose the merging done around it, so the result can be checked before
+/// deciding whether to borrow or own.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::normalize_ranges;
+///
+/// assert_eq!(normalize_ranges(&[0..2, 3..5, 5..7]), vec![0..2, 3..7]);
+/// assert_eq!(normalize_ranges(&[0..5, 3..8, 8..8, 2..4]), vec![0..8]);
+/// assert_eq!(normalize_ranges(&[6..11, 0..5]), vec![6..11, 0..5]);
+/// ```
+#[must_use]
+pub fn normalize_ranges(ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut normalized: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+    for range in ranges.iter().filter(|r| r.start < r.end) {
+        if let Some(last) = normalized.last_mut() {
+            if range.start <= last.end && last.start <= range.end {
+                last.start = last.start.min(range.start);
+                last.end = last.end.max(range.end);
+                continue;
+            }
+        }
+        normalized.push(range.clone());
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+    use crate::{Borrowed, Strloin};
+
+    #[test]
+    fn ranges() {
+        macro_rules! ranges_ok {
+            ($input:expr, $expected_collapse:expr, $expected_ranges:expr) => {
+                let input = $input;
+
+                assert_eq!(
+                    collapse_ranges(input),
+                    $expected_collapse,
+                    "collapse_ranges"
+                );
+                assert_eq!(
+                    collapse_ranges_iter(input.iter().cloned()),
+                    $expected_collapse,
+                    "collapse_ranges_iter"
+                );
+
+                let mut ranges = Ranges::new();
+                for range in input {
+                    ranges.push(range.clone());
+                }
+
+                assert_eq!(ranges.ranges, $expected_ranges, "Ranges");
+            };
+        }
+
+        ranges_ok!(&[], Some(0..0), &[]);
+
+        ranges_ok!(&[0..0], Some(0..0), &[0..0]);
+        ranges_ok!(&[0..2], Some(0..2), &[0..2]);
+        ranges_ok!(&[2..0], None, &[2..0]);
+
+        ranges_ok!(&[0..2, 2..4], Some(0..4), &[0..4]);
+        ranges_ok!(&[3..2, 2..4], None, &[3..2, 2..4]);
+        ranges_ok!(&[0..2, 4..6], None, &[0..2, 4..6]);
+        ranges_ok!(&[0..2, 3..4], None, &[0..2, 3..4]);
+        ranges_ok!(&[2..4, 0..2], None, &[2..4, 0..2]);
+        ranges_ok!(&[0..2, 0..4], None, &[0..2, 0..4]);
+        ranges_ok!(&[0..2, 2..1], None, &[0..2, 2..1]);
+
+        ranges_ok!(&[0..2, 2..4, 4..6], Some(0..6), &[0..6]);
+        ranges_ok!(&[0..2, 3..5, 6..8], None, &[0..2, 3..5, 6..8]);
+        ranges_ok!(&[0..2, 3..5, 5..7], None, &[0..2, 3..7]);
+
+        ranges_ok!(&[0..0, 2..4], Some(2..4), &[2..4]);
+        ranges_ok!(&[2..4, 0..0], Some(2..4), &[2..4]);
+        ranges_ok!(&[0..5, 7..7, 5..11], Some(0..11), &[0..11]);
+        ranges_ok!(&[3..3, 5..5], Some(3..3), &[3..3]);
+        ranges_ok!(&[0..2, 3..3, 4..6], None, &[0..2, 4..6]);
+        ranges_ok!(&[0..0, 2..1], None, &[2..1]);
+    }
+
+    #[test]
+    fn collapse_ranges_iter_stops_early() {
+        let infinite = (0..).map(|i| i * 2..i * 2 + 1);
+        assert_eq!(collapse_ranges_iter(infinite), None);
+    }
+
+    #[test]
+    fn collapse_ranges_const() {
+        const TABLE: [Range<usize>; 4] = [0..4, 4..4, 4..8, 8..16];
+        const COLLAPSED: Option<Range<usize>> = collapse_ranges(&TABLE);
+        const BROKEN: Option<Range<usize>> = collapse_ranges(&[0..4, 5..8]);
+        assert_eq!(COLLAPSED, Some(0..16));
+        assert_eq!(BROKEN, None);
+    }
+
+    #[test]
+    fn try_push() {
+        let mut ranges = Ranges::new();
+        assert_eq!(ranges.try_push(3..3), Ok(()));
+        assert_eq!(ranges.try_push(0..5), Ok(()));
+        assert_eq!(ranges.try_push(5..8), Ok(()));
+        assert_eq!(
+            ranges.try_push(9..8),
+            Err(Error::Inverted {
+                index: 1,
+                range: 9..8
+            })
+        );
+        assert_eq!(ranges.try_push(2..4), Ok(()));
+        assert_eq!(ranges.ranges, [0..8, 2..4]);
+    }
+
+    #[test]
+    fn try_push_ordered() {
+        let mut ranges = Ranges::new();
+        assert_eq!(ranges.try_push_ordered(0..5), Ok(()));
+        assert_eq!(ranges.try_push_ordered(5..5), Ok(()));
+        assert_eq!(ranges.try_push_ordered(5..8), Ok(()));
+        assert_eq!(
+            ranges.try_push_ordered(7..9),
+            Err(Error::OutOfOrder {
+                index: 1,
+                range: 7..9,
+                previous: 0..8
+            })
+        );
+        assert_eq!(
+            ranges.try_push_ordered(10..9),
+            Err(Error::Inverted {
+                index: 1,
+                range: 10..9
+            })
+        );
+        assert_eq!(
+            ranges.try_push_ordered(6..5),
+            Err(Error::OutOfOrder {
+                index: 1,
+                range: 6..5,
+                previous: 0..8
+            })
+        );
+        assert_eq!(ranges.try_push_ordered(8..9), Ok(()));
+        assert_eq!(ranges.ranges, [0..9]);
+    }
+
+    #[test]
+    fn push_len() {
+        let mut ranges = Ranges::new();
+        ranges.push_len(0, 0);
+        ranges.push_len(2, 3);
+        ranges.push_len(5, 1);
+        ranges.push_len(usize::MAX, 0);
+        assert!(ranges.try_push_len(1, usize::MAX).is_err());
+        assert_eq!(ranges.ranges, [2..6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "with length 18446744073709551615 overflows")]
+    #[cfg(target_pointer_width = "64")]
+    fn push_len_overflow() {
+        let mut ranges = Ranges::new();
+        ranges.push_len(1, usize::MAX);
+    }
+
+    #[test]
+    fn push_unchecked() {
+        let mut ranges = Ranges::new();
+        unsafe {
+            ranges.push_unchecked(0..5);
+            ranges.push_unchecked(5..8);
+            ranges.push_unchecked(2..2);
+        }
+        assert_eq!(ranges.ranges, [0..8]);
+
+        let mut ranges = Ranges::new();
+        unsafe {
+            ranges.push_unchecked(3..3);
+            ranges.push_unchecked(0..5);
+            ranges.push_unchecked(7..9);
+        }
+        assert_eq!(ranges.ranges, [0..5, 7..9]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "push_unchecked given inverted range 8..4")]
+    fn push_unchecked_inverted() {
+        let mut ranges = Ranges::from(0..5);
+        unsafe { ranges.push_unchecked(8..4) };
+    }
+
+    #[test]
+    fn push_range_checked() {
+        let mut ranges = Ranges::new();
+        assert_eq!(ranges.push_range_checked(2_u8, 3), Ok(()));
+        assert_eq!(ranges.push_range_checked(5_u32, 1), Ok(()));
+        assert_eq!(ranges.push_range_checked(8_i32, 1), Ok(()));
+        assert_eq!(
+            ranges.push_range_checked(9_i32, -1),
+            Err(Error::OffsetOverflow {
+                index: 2,
+                field: "len",
+                value: "-1".to_owned()
+            })
+        );
+        assert_eq!(
+            ranges.push_range_checked(u128::MAX, 0),
+            Err(Error::OffsetOverflow {
+                index: 2,
+                field: "start",
+                value: u128::MAX.to_string()
+            })
+        );
+        assert_eq!(
+            ranges.push_range_checked(usize::MAX, 1),
+            Err(Error::LengthOverflow {
+                index: 2,
+                start: usize::MAX,
+                len: 1
+            })
+        );
+        assert_eq!(ranges.ranges, [2..6, 8..9]);
+    }
+
+    #[test]
+    fn push_merge() {
+        macro_rules! push_merge_ok {
+            ($input:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                for range in $input {
+                    ranges.push_merge(range);
+                }
+                assert_eq!(ranges.ranges, $expected, "push_merge");
+            };
+        }
+
+        push_merge_ok!(Vec::<Range<usize>>::new(), []);
+        push_merge_ok!([0..5], [0..5]);
+        push_merge_ok!([0..5, 5..8], [0..8]);
+        push_merge_ok!([0..5, 3..8], [0..8]);
+        push_merge_ok!([0..5, 1..3], [0..5]);
+        push_merge_ok!([0..5, 0..5], [0..5]);
+        push_merge_ok!([3..8, 0..5], [0..8]);
+        push_merge_ok!([3..8, 0..3], [0..8]);
+        push_merge_ok!([0..5, 6..8], [0..5, 6..8]);
+        push_merge_ok!([6..8, 0..5], [6..8, 0..5]);
+        push_merge_ok!([0..5, 3..3], [0..5, 3..3]);
+        push_merge_ok!([2..2, 0..5], [2..2, 0..5]);
+        push_merge_ok!([0..2, 4..6, 1..5], [0..2, 1..6]);
+        push_merge_ok!([5..3, 4..6], [5..3, 4..6]);
+    }
+
+    #[test]
+    fn extend() {
+        let mut ranges = Ranges::new();
+        ranges.extend(vec![0..2, 2..5]);
+        assert_eq!(ranges.ranges, [0..5], "extend");
+    }
+
+    #[test]
+    fn extend_from_slice() {
+        macro_rules! extend_from_slice_ok {
+            ($before:expr, $input:expr) => {
+                let before: &[Range<usize>] = &$before;
+                let input: &[Range<usize>] = &$input;
+
+                let mut expected = Ranges::from(before);
+                for range in input {
+                    expected.push(range.clone());
+                }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+                let mut got = Ranges::from(before);
+                got.extend_from_slice(input);
+                assert_eq!(got.ranges, expected.ranges, "{:?} + {:?}", before, input);
+            };
+        }
+
+        extend_from_slice_ok!([], []);
+        extend_from_slice_ok!([0..5], []);
+        extend_from_slice_ok!([], [0..5]);
+        extend_from_slice_ok!([0..5], [5..8]);
+        extend_from_slice_ok!([0..5], [6..8, 8..9, 9..9, 9..10]);
+        extend_from_slice_ok!([0..0], [0..5, 5..7]);
+        extend_from_slice_ok!([0..2, 4..4], [4..6, 1..0, 0..3]);
+    }
 
     #[test]
-    fn ranges() {
-        macro_rules! ranges_ok {
-            ($input:expr, $expected_collapse:expr, $expected_ranges:expr) => {
-                let input = $input;
+    fn retain() {
+        macro_rules! retain_ok {
+            ($input:expr, $f:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                ranges.retain($f);
+                assert_eq!(ranges.ranges, $expected, "retain");
+            };
+        }
+
+        retain_ok!(vec![], |_| false, []);
+        retain_ok!(vec![0..5, 6..8], |_| true, [0..5, 6..8]);
+        retain_ok!(vec![0..5, 6..8], |_| false, []);
+        retain_ok!(vec![0..5, 6..8, 2..3], |r| r.start > 0, [6..8, 2..3]);
+        retain_ok!(vec![0..2, 9..9, 2..4], |r| !r.is_empty(), [0..4]);
+        retain_ok!(vec![0..2, 5..6, 2..4, 4..5], |r| *r != (5..6), [0..5]);
+    }
 
+    #[test]
+    fn dedup() {
+        macro_rules! dedup_ok {
+            ($input:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                ranges.dedup();
+                assert_eq!(ranges.ranges, $expected, "dedup");
+            };
+        }
+
+        dedup_ok!(vec![], []);
+        dedup_ok!(vec![0..5], [0..5]);
+        dedup_ok!(vec![0..5, 0..5], [0..5]);
+        dedup_ok!(vec![0..5, 6..7, 0..5], [0..5, 6..7, 0..5]);
+        dedup_ok!(vec![3..3, 3..3], [3..3]);
+        dedup_ok!(vec![4..1, 4..1], [4..1]);
+        dedup_ok!(vec![0..2, 0..2, 2..4], [0..4]);
+    }
+
+    #[test]
+    fn find_all() {
+        macro_rules! find_all_ok {
+            ($source:expr, $needle:expr, $expected:expr) => {
+                let got = Ranges::find_all($source, $needle);
                 assert_eq!(
-                    collapse_ranges(input),
-                    $expected_collapse,
-                    "collapse_ranges"
+                    got.ranges, $expected,
+                    "find_all({:?}, {:?})",
+                    $source, $needle
+                );
+            };
+        }
+
+        find_all_ok!("", "a", []);
+        find_all_ok!("hello world", "x", []);
+        find_all_ok!("hello world", "o", [4..5, 7..8]);
+        find_all_ok!("hello world", "hello world", [0..11]);
+        find_all_ok!("aaaa", "aa", [0..4]);
+        find_all_ok!("aaa", "aa", [0..2]);
+        find_all_ok!("naïve café", "é", [10..12]);
+        find_all_ok!("ab", "", [0..0]);
+    }
+
+    #[test]
+    fn from_split() {
+        macro_rules! from_split_ok {
+            ($source:expr, $delimiter:expr, $expected:expr) => {
+                let source: &str = $source;
+                let got = Ranges::from_split(source, $delimiter);
+                assert_eq!(
+                    got.ranges, $expected,
+                    "from_split({:?}, {:?})",
+                    source, $delimiter
                 );
 
+                let fields: Vec<_> = got.iter().map(|r| &source[r.clone()]).collect();
+                let expected: Vec<_> = source.split($delimiter).collect();
+                assert_eq!(fields, expected, "same fields as str::split");
+            };
+        }
+
+        from_split_ok!("", ",", [0..0]);
+        from_split_ok!("abc", ",", [0..3]);
+        from_split_ok!("a,b", ",", [0..1, 2..3]);
+        from_split_ok!(",a,", ",", [0..0, 1..2, 3..3]);
+        from_split_ok!("a, b, c", ", ", [0..1, 3..4, 6..7]);
+        from_split_ok!("ünï→cödé", "→", [0..5, 8..14]);
+        from_split_ok!("ab", "", [0..0, 0..1, 1..2, 2..2]);
+    }
+
+    #[test]
+    fn from_char_filter() {
+        macro_rules! from_char_filter_ok {
+            ($source:expr, $f:expr, $expected:expr) => {
+                let got = Ranges::from_char_filter($source, $f);
+                assert_eq!(got.ranges, $expected, "from_char_filter({:?})", $source);
+            };
+        }
+
+        from_char_filter_ok!("", char::is_alphanumeric, []);
+        from_char_filter_ok!("hello", char::is_alphanumeric, [0..5]);
+        from_char_filter_ok!("  ", char::is_alphanumeric, []);
+        from_char_filter_ok!("a b  c", char::is_whitespace, [1..2, 3..5]);
+        from_char_filter_ok!("x→y←z", |c: char| !c.is_ascii(), [1..4, 5..8]);
+        from_char_filter_ok!("日本 語", char::is_alphabetic, [0..6, 7..10]);
+    }
+
+    #[test]
+    fn from_mask() {
+        macro_rules! from_mask_ok {
+            ($mask:expr, $expected:expr) => {
+                let mask: &[bool] = &$mask;
+                assert_eq!(Ranges::from_mask(mask).ranges, $expected, "from_mask");
+
+                let mut words = vec![0_u64; (mask.len() + 63) / 64];
+                for (i, &selected) in mask.iter().enumerate() {
+                    if selected {
+                        words[i / 64] |= 1 << (i % 64);
+                    }
+                }
+                let got = Ranges::from_bits(&words, mask.len());
+                assert_eq!(got.ranges, $expected, "from_bits");
+            };
+        }
+
+        from_mask_ok!([], []);
+        from_mask_ok!([false, false], []);
+        from_mask_ok!([true], [0..1]);
+        from_mask_ok!([true, true, false], [0..2]);
+        from_mask_ok!([false, true, false, true], [1..2, 3..4]);
+        from_mask_ok!([true; 64], [0..64]);
+        from_mask_ok!([true; 130], [0..130]);
+
+        let mut mask = [false; 200];
+        let mut seed = 17_u32;
+        for selected in &mut mask {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            *selected = seed >> 16 & 3 != 0;
+        }
+        let expected: Vec<_> = Ranges::from_mask(&mask).ranges;
+        assert!(expected.len() > 10);
+        from_mask_ok!(mask, expected);
+    }
+
+    #[test]
+    fn from_bits() {
+        assert_eq!(Ranges::from_bits(&[], 10).ranges, []);
+        assert_eq!(Ranges::from_bits(&[u64::MAX], 10).ranges, [0..10]);
+        assert_eq!(Ranges::from_bits(&[u64::MAX], 100).ranges, [0..64]);
+        assert_eq!(Ranges::from_bits(&[1 << 63, 1], 65).ranges, [63..65]);
+        assert_eq!(Ranges::from_bits(&[1 << 63, 1], 64).ranges, [63..64]);
+        assert_eq!(Ranges::from_bits(&[0, 0, 0b100], 200).ranges, [130..131]);
+    }
+
+    #[test]
+    fn len() {
+        let ranges = Ranges::new();
+        assert_eq!(
+            (ranges.len(), ranges.total_len(), ranges.is_empty()),
+            (0, 0, true)
+        );
+
+        let ranges = Ranges::from(3..3);
+        assert_eq!(
+            (ranges.len(), ranges.total_len(), ranges.is_empty()),
+            (1, 0, false)
+        );
+
+        let ranges: Ranges = [0..2, 2..5, 1..4, 6..6].into_iter().collect();
+        assert_eq!(
+            (ranges.len(), ranges.total_len(), ranges.is_empty()),
+            (2, 8, false)
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        macro_rules! coverage_ok {
+            ($input:expr, $covered_len:expr, $run_count:expr) => {
                 let mut ranges = Ranges::new();
-                for range in input {
-                    ranges.push(range.clone());
+                ranges.ranges = $input;
+                assert_eq!(ranges.covered_len(), $covered_len, "covered_len");
+                assert_eq!(ranges.run_count(), $run_count, "run_count");
+            };
+        }
+
+        coverage_ok!(vec![], 0, 0);
+        coverage_ok!(vec![0..0, 3..3, 5..1], 0, 0);
+        coverage_ok!(vec![0..5], 5, 1);
+        coverage_ok!(vec![0..5, 5..8], 8, 1);
+        coverage_ok!(vec![0..5, 5..5, 5..8], 8, 1);
+        coverage_ok!(vec![0..5, 6..8], 7, 2);
+        coverage_ok!(vec![0..5, 0..5], 5, 2);
+        coverage_ok!(vec![6..8, 0..5, 2..7], 8, 3);
+    }
+
+    #[test]
+    fn edit() {
+        let mut ranges: Ranges = [0..2, 3..5, 6..8].into_iter().collect();
+
+        ranges.insert(1, 2..3);
+        assert_eq!(ranges.ranges, [0..2, 2..3, 3..5, 6..8], "insert");
+
+        assert_eq!(ranges.remove(2), 3..5, "remove");
+        assert_eq!(ranges.ranges, [0..2, 2..3, 6..8], "remove");
+
+        assert_eq!(ranges.pop(), Some(6..8), "pop");
+        assert_eq!(ranges.ranges, [0..2, 2..3], "pop");
+
+        ranges.truncate(5);
+        assert_eq!(ranges.ranges, [0..2, 2..3], "truncate past end");
+        ranges.truncate(1);
+        assert_eq!(ranges.ranges, [0..2], "truncate");
+
+        assert_eq!(ranges.pop(), Some(0..2), "pop last");
+        assert_eq!(ranges.pop(), None, "pop empty");
+    }
+
+    #[test]
+    fn rollback() {
+        macro_rules! rollback_ok {
+            ($before:expr, $after:expr) => {
+                let mut ranges: Ranges = $before.into_iter().collect();
+                let expected = ranges.ranges.clone();
+                let checkpoint = ranges.checkpoint();
+                ranges.extend($after);
+                ranges.rollback(checkpoint);
+                assert_eq!(ranges.ranges, expected, "rollback");
+            };
+        }
+
+        rollback_ok!(Vec::<Range<usize>>::new(), vec![]);
+        rollback_ok!(Vec::<Range<usize>>::new(), vec![0..2, 2..4]);
+        rollback_ok!(vec![0..2], vec![]);
+        rollback_ok!(vec![0..2], vec![2..4]);
+        rollback_ok!(vec![0..2], vec![2..4, 4..6, 7..9, 9..10]);
+        rollback_ok!(vec![0..2], vec![3..4]);
+        rollback_ok!(vec![0..2, 4..6], vec![6..8]);
+        rollback_ok!(vec![0..2, 4..4], vec![4..6]);
+
+        let mut ranges = Ranges::from(0..2);
+        let outer = ranges.checkpoint();
+        ranges.push(2..4);
+        let inner = ranges.checkpoint();
+        ranges.push(4..6);
+        ranges.push(8..9);
+        ranges.rollback(inner);
+        assert_eq!(ranges.ranges, [0..4], "nested rollback");
+        ranges.rollback(outer);
+        assert_eq!(ranges.ranges, [0..2], "outer rollback");
+    }
+
+    #[test]
+    fn shift_by() {
+        let mut ranges = Ranges {
+            ranges: vec![2..5, 6..6, 8..7],
+        };
+        ranges.shift_by(0);
+        assert_eq!(ranges.ranges, [2..5, 6..6, 8..7], "shift by 0");
+        ranges.shift_by(3);
+        assert_eq!(ranges.ranges, [5..8, 9..9, 11..10], "shift up");
+        ranges.shift_by(-5);
+        assert_eq!(ranges.ranges, [0..3, 4..4, 6..5], "shift down");
+
+        assert_eq!(
+            ranges.try_shift_by(-5),
+            Err(Error::Overflow {
+                index: 0,
+                range: 0..3
+            }),
+            "underflow"
+        );
+        assert_eq!(ranges.ranges, [0..3, 4..4, 6..5], "unchanged on underflow");
+
+        let mut ranges: Ranges = [0..3, usize::MAX - 2..usize::MAX].into_iter().collect();
+        assert_eq!(
+            ranges.try_shift_by(1),
+            Err(Error::Overflow {
+                index: 1,
+                range: usize::MAX - 2..usize::MAX
+            }),
+            "overflow"
+        );
+        assert_eq!(
+            ranges.ranges,
+            [0..3, usize::MAX - 2..usize::MAX],
+            "unchanged on overflow"
+        );
+
+        let mut ranges = Ranges::from(usize::MAX..usize::MAX);
+        assert_eq!(ranges.try_shift_by(isize::MIN), Ok(()));
+        let magnitude = isize::MIN.unsigned_abs();
+        assert_eq!(
+            ranges.ranges,
+            [usize::MAX - magnitude..usize::MAX - magnitude]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot shift by -1")]
+    fn shift_by_underflow() {
+        let mut ranges = Ranges::from(0..2);
+        ranges.shift_by(-1);
+    }
+
+    #[test]
+    fn clamp_to() {
+        macro_rules! clamp_ok {
+            ($input:expr, $len:expr, $expected:expr, $modified:expr) => {
+                let mut ranges: Ranges = $input.into_iter().collect();
+                assert_eq!(ranges.clamp_to($len), $modified, "modified count");
+                assert_eq!(ranges.ranges, $expected, "clamped");
+            };
+        }
+
+        clamp_ok!(Vec::<Range<usize>>::new(), 5, [], 0);
+        clamp_ok!(vec![0..5], 5, [0..5], 0);
+        clamp_ok!(vec![0..5], 0, [], 1);
+        clamp_ok!(vec![5..5], 5, [5..5], 0);
+        clamp_ok!(vec![0..8], 5, [0..5], 1);
+        clamp_ok!(vec![5..8], 5, [], 1);
+        clamp_ok!(vec![0..2, 6..8, 3..9], 5, [0..2, 3..5], 2);
+        clamp_ok!(vec![3..1, 9..7], 5, [3..1], 1);
+    }
+
+    #[test]
+    fn normalize() {
+        macro_rules! normalize_ok {
+            ($input:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                ranges.normalize();
+                assert_eq!(ranges.ranges, $expected, "normalize");
+            };
+        }
+
+        normalize_ok!(vec![], []);
+        normalize_ok!(vec![0..0], []);
+        normalize_ok!(vec![3..1], []);
+        normalize_ok!(vec![0..5], [0..5]);
+        normalize_ok!(vec![0..2, 2..5], [0..5]);
+        normalize_ok!(vec![2..5, 0..2], [0..5]);
+        normalize_ok!(vec![0..5, 1..3], [0..5]);
+        normalize_ok!(vec![1..3, 0..5], [0..5]);
+        normalize_ok!(vec![0..3, 2..5], [0..5]);
+        normalize_ok!(vec![0..2, 3..5], [0..2, 3..5]);
+        normalize_ok!(vec![3..5, 0..2], [0..2, 3..5]);
+        normalize_ok!(vec![0..2, 3..5, 5..7], [0..2, 3..7]);
+        normalize_ok!(vec![0..2, 2..2, 2..4], [0..4]);
+        normalize_ok!(vec![8..9, 0..2, 7..7, 4..6, 1..5, 9..12], [0..6, 8..12]);
+    }
+
+    #[test]
+    fn normalize_ranges() {
+        macro_rules! normalize_ranges_ok {
+            ($input:expr, $expected:expr) => {
+                let input: &[Range<usize>] = &$input;
+                let normalized = super::normalize_ranges(input);
+                assert_eq!(normalized, $expected, "normalize_ranges");
+
+                if let Some(collapsed) = collapse_ranges(input) {
+                    assert_eq!(
+                        collapse_ranges(&normalized),
+                        Some(collapsed),
+                        "collapses the same"
+                    );
                 }
+            };
+        }
 
-                assert_eq!(ranges.ranges, $expected_ranges, "Ranges");
+        normalize_ranges_ok!([], []);
+        normalize_ranges_ok!([0..0], []);
+        normalize_ranges_ok!([3..1], []);
+        normalize_ranges_ok!([0..5], [0..5]);
+        normalize_ranges_ok!([0..2, 2..5], [0..5]);
+        normalize_ranges_ok!([2..5, 0..2], [0..5]);
+        normalize_ranges_ok!([0..5, 1..3], [0..5]);
+        normalize_ranges_ok!([1..3, 0..5], [0..5]);
+        normalize_ranges_ok!([0..2, 3..5], [0..2, 3..5]);
+        normalize_ranges_ok!([3..5, 0..2], [3..5, 0..2]);
+        normalize_ranges_ok!([0..2, 3..5, 5..7], [0..2, 3..7]);
+        normalize_ranges_ok!([0..2, 2..2, 2..4], [0..4]);
+        normalize_ranges_ok!([0..2, 5..1, 2..4], [0..4]);
+        normalize_ranges_ok!([0..2, 4..6, 1..3], [0..2, 4..6, 1..3]);
+    }
+
+    #[test]
+    fn contains() {
+        macro_rules! contains_ok {
+            ($input:expr, $len:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                for i in 0..$len {
+                    let expected = ranges.iter().any(|r| r.contains(&i));
+                    assert_eq!(ranges.contains(i), expected, "contains({})", i);
+                }
             };
         }
 
-        ranges_ok!(&[], Some(0..0), &[]);
+        contains_ok!(vec![], 3);
+        contains_ok!(vec![0..5], 7);
+        contains_ok!(vec![2..2], 4);
+        contains_ok!(vec![1..3, 3..3, 5..6], 8);
+        contains_ok!(vec![0..1, 2..3, 4..5, 6..7, 8..9], 10);
+        contains_ok!(vec![0..2, 4..4, 6..9], 10);
+    }
 
-        ranges_ok!(&[0..0], Some(0..0), &[0..0]);
-        ranges_ok!(&[0..2], Some(0..2), &[0..2]);
-        ranges_ok!(&[2..0], None, &[2..0]);
+    #[test]
+    fn overlaps() {
+        macro_rules! overlaps_ok {
+            ($input:expr, $len:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                for start in 0..$len {
+                    for end in start..$len {
+                        let covered: Vec<_> = (start..end).map(|i| ranges.contains(i)).collect();
+                        assert_eq!(
+                            ranges.overlaps(start..end),
+                            covered.iter().any(|&c| c),
+                            "overlaps({:?})",
+                            start..end
+                        );
+                        assert_eq!(
+                            ranges.contains_range(start..end),
+                            covered.iter().all(|&c| c),
+                            "contains_range({:?})",
+                            start..end
+                        );
+                    }
+                }
+            };
+        }
 
-        ranges_ok!(&[0..2, 2..4], Some(0..4), &[0..4]);
-        ranges_ok!(&[3..2, 2..4], None, &[3..2, 2..4]);
-        ranges_ok!(&[0..2, 4..6], None, &[0..2, 4..6]);
-        ranges_ok!(&[0..2, 3..4], None, &[0..2, 3..4]);
-        ranges_ok!(&[2..4, 0..2], None, &[2..4, 0..2]);
-        ranges_ok!(&[0..2, 0..4], None, &[0..2, 0..4]);
-        ranges_ok!(&[0..2, 2..1], None, &[0..2, 2..1]);
+        overlaps_ok!(vec![], 3);
+        overlaps_ok!(vec![0..5], 7);
+        overlaps_ok!(vec![2..2], 4);
+        overlaps_ok!(vec![1..3, 3..3, 5..6], 8);
+        overlaps_ok!(vec![0..1, 2..3, 4..5, 6..7, 8..9], 10);
+        overlaps_ok!(vec![0..2, 4..4, 6..9], 10);
+        overlaps_ok!(vec![0..2, 2..4], 6);
+        overlaps_ok!(vec![1..3, 3..3, 3..5, 6..7, 7..9], 10);
 
-        ranges_ok!(&[0..2, 2..4, 4..6], Some(0..6), &[0..6]);
-        ranges_ok!(&[0..2, 3..5, 6..8], None, &[0..2, 3..5, 6..8]);
-        ranges_ok!(&[0..2, 3..5, 5..7], None, &[0..2, 3..7]);
+        let ranges = Ranges {
+            ranges: vec![0..2, 2..4],
+        };
+        assert!(ranges.contains_range(1..3));
+        assert!(!ranges.contains_range(1..5));
+
+        let ranges = Ranges::from(2..5);
+        assert!(!ranges.overlaps(4..3));
+        assert!(ranges.contains_range(9..1));
     }
 
     #[test]
-    fn extend() {
-        let mut ranges = Ranges::new();
-        ranges.extend(vec![0..2, 2..5]);
-        assert_eq!(ranges.ranges, [0..5], "extend");
+    fn split_at() {
+        macro_rules! split_at_ok {
+            ($input:expr, $pos:expr, $left:expr, $right:expr) => {
+                let ranges: Ranges = $input.into_iter().collect();
+                let (left, right) = ranges.split_at($pos);
+                assert_eq!(left.ranges, $left, "left");
+                assert_eq!(right.ranges, $right, "right");
+                assert_eq!(left.total_len() + right.total_len(), ranges.total_len());
+            };
+        }
+
+        split_at_ok!(Vec::<Range<usize>>::new(), 3, [], []);
+        split_at_ok!([0..5], 0, [], [0..5]);
+        split_at_ok!([0..5], 5, [0..5], []);
+        split_at_ok!([0..5], 2, [0..2], [2..5]);
+        split_at_ok!([0..2, 3..5], 2, [0..2], [3..5]);
+        split_at_ok!([0..2, 3..5], 3, [0..2], [3..5]);
+        split_at_ok!([0..2, 3..5, 0..4], 1, [0..1, 0..1], [1..2, 3..5, 1..4]);
+        split_at_ok!([2..2], 3, [2..2], []);
+        split_at_ok!([3..3], 3, [], [3..3]);
+    }
+
+    #[test]
+    fn gaps() {
+        macro_rules! gaps_ok {
+            ($input:expr, $len:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                let gaps: Vec<_> = ranges.gaps($len).collect();
+                assert_eq!(gaps, $expected, "gaps");
+            };
+        }
+
+        gaps_ok!(vec![], 0, []);
+        gaps_ok!(vec![], 5, [0..5]);
+        gaps_ok!(vec![0..5], 5, []);
+        gaps_ok!(vec![1..4], 5, [0..1, 4..5]);
+        gaps_ok!(vec![0..2, 2..5], 5, []);
+        gaps_ok!(vec![0..2, 3..5], 5, [2..3]);
+        gaps_ok!(vec![0..3, 1..2, 4..5], 6, [3..4, 5..6]);
+        gaps_ok!(vec![2..2, 3..3, 4..1], 4, [0..4]);
+        gaps_ok!(vec![1..3, 7..9], 5, [0..1, 3..5]);
+        gaps_ok!(vec![0..9], 5, []);
+    }
+
+    #[test]
+    fn complement() {
+        macro_rules! complement_ok {
+            ($input:expr, $len:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                let complement = ranges.complement($len);
+                assert_eq!(complement.ranges, $expected, "complement");
+            };
+        }
+
+        complement_ok!(vec![], 0, []);
+        complement_ok!(vec![], 5, [0..5]);
+        complement_ok!(vec![0..5], 5, []);
+        complement_ok!(vec![3..5, 0..2], 5, [2..3]);
+        complement_ok!(vec![1..4, 0..2], 6, [4..6]);
+        complement_ok!(vec![4..9, 2..2, 1..0], 6, [0..4]);
+    }
+
+    #[test]
+    fn union() {
+        macro_rules! union_ok {
+            ($a:expr, $b:expr, $expected:expr) => {
+                let a: Ranges = $a.into_iter().collect();
+                let b: Ranges = $b.into_iter().collect();
+                assert_eq!(a.union(&b).ranges, $expected, "a | b");
+                assert_eq!(b.union(&a).ranges, $expected, "b | a");
+            };
+        }
+
+        union_ok!(Vec::<Range<usize>>::new(), Vec::<Range<usize>>::new(), []);
+        union_ok!([0..5], Vec::<Range<usize>>::new(), [0..5]);
+        union_ok!([0..5], [0..5], [0..5]);
+        union_ok!([0..2], [2..5], [0..5]);
+        union_ok!([0..2], [3..5], [0..2, 3..5]);
+        union_ok!([1..4], [0..5], [0..5]);
+        union_ok!([6..8, 0..2], [1..3, 3..3], [0..3, 6..8]);
+        union_ok!([0..2, 4..6, 8..10], [1..9], [0..10]);
+    }
+
+    #[test]
+    fn intersection() {
+        macro_rules! intersection_ok {
+            ($a:expr, $b:expr, $expected:expr) => {
+                let a: Ranges = $a.into_iter().collect();
+                let b: Ranges = $b.into_iter().collect();
+                assert_eq!(a.intersection(&b).ranges, $expected, "a & b");
+                assert_eq!(b.intersection(&a).ranges, $expected, "b & a");
+            };
+        }
+
+        intersection_ok!(Vec::<Range<usize>>::new(), Vec::<Range<usize>>::new(), []);
+        intersection_ok!([0..5], Vec::<Range<usize>>::new(), []);
+        intersection_ok!([0..5], [0..5], [0..5]);
+        intersection_ok!([0..2], [2..5], []);
+        intersection_ok!([0..3], [2..5], [2..3]);
+        intersection_ok!([1..4], [0..5], [1..4]);
+        intersection_ok!([2..2], [0..5], []);
+        intersection_ok!([0..2, 4..6, 8..10], [1..9], [1..2, 4..6, 8..9]);
+        intersection_ok!([6..9, 0..4], [3..7, 1..2], [1..2, 3..4, 6..7]);
+        intersection_ok!([0..4, 2..6], [5..8, 3..4], [3..4, 5..6]);
+    }
+
+    #[test]
+    fn difference() {
+        macro_rules! difference_ok {
+            ($a:expr, $b:expr, $expected:expr) => {
+                let a: Ranges = $a.into_iter().collect();
+                let b: Ranges = $b.into_iter().collect();
+                assert_eq!(a.difference(&b).ranges, $expected, "a - b");
+            };
+        }
+
+        difference_ok!(Vec::<Range<usize>>::new(), Vec::<Range<usize>>::new(), []);
+        difference_ok!(Vec::<Range<usize>>::new(), [0..5], []);
+        difference_ok!([0..5], Vec::<Range<usize>>::new(), [0..5]);
+        difference_ok!([0..5], [0..5], []);
+        difference_ok!([0..5], [1..4], [0..1, 4..5]);
+        difference_ok!([1..4], [0..5], []);
+        difference_ok!([0..5], [5..8], [0..5]);
+        difference_ok!([0..5], [3..8], [0..3]);
+        difference_ok!([3..8], [0..5], [5..8]);
+        difference_ok!([0..5], [2..2], [0..5]);
+        difference_ok!([0..3, 5..8, 10..12], [2..6, 7..11], [0..2, 6..7, 11..12]);
+        difference_ok!([5..8, 0..3], [1..2, 1..2], [0..1, 2..3, 5..8]);
+        difference_ok!([0..10], [1..2, 3..4, 5..6], [0..1, 2..3, 4..5, 6..10]);
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        macro_rules! symmetric_difference_ok {
+            ($a:expr, $b:expr, $expected:expr) => {
+                let a: Ranges = $a.into_iter().collect();
+                let b: Ranges = $b.into_iter().collect();
+                assert_eq!(a.symmetric_difference(&b).ranges, $expected, "a ^ b");
+                assert_eq!(b.symmetric_difference(&a).ranges, $expected, "b ^ a");
+            };
+        }
+
+        symmetric_difference_ok!(Vec::<Range<usize>>::new(), Vec::<Range<usize>>::new(), []);
+        symmetric_difference_ok!([0..5], Vec::<Range<usize>>::new(), [0..5]);
+        symmetric_difference_ok!([0..5], [0..5], []);
+        symmetric_difference_ok!([0..2], [2..5], [0..5]);
+        symmetric_difference_ok!([0..3], [2..5], [0..2, 3..5]);
+        symmetric_difference_ok!([1..4], [0..5], [0..1, 4..5]);
+        symmetric_difference_ok!([0..2, 4..6], [1..5], [0..1, 2..4, 5..6]);
+    }
+
+    #[test]
+    fn display() {
+        macro_rules! display_ok {
+            ($input:expr, $expected:expr) => {
+                let ranges: Ranges = $input.into_iter().collect();
+                let text = ranges.to_string();
+                assert_eq!(text, $expected, "display");
+                assert_eq!(
+                    text.parse::<Ranges>().map(|r| r.ranges),
+                    Ok(ranges.ranges),
+                    "round trip"
+                );
+            };
+        }
+
+        display_ok!(Vec::<Range<usize>>::new(), "");
+        display_ok!([0..0], "0..0");
+        display_ok!([0..5], "0..5");
+        display_ok!([0..5, 6..11], "0..5,6..11");
+        display_ok!([6..11, 0..0, 0..5], "6..11,0..5");
+
+        let ranges = Ranges {
+            ranges: vec![0..3, 3..5, 7..7],
+        };
+        assert_eq!(ranges.to_string(), "0..3,3..5,7..7");
+        assert_eq!(
+            ranges.to_string().parse::<Ranges>().map(|r| r.ranges),
+            Ok(ranges.ranges),
+            "uncollapsed round trip"
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        macro_rules! from_str_ok {
+            ($input:expr, $expected:expr) => {
+                let got = $input.parse::<Ranges>().map(|ranges| ranges.ranges);
+                assert_eq!(got, $expected, "from_str({:?})", $input);
+            };
+        }
+
+        fn syntax(index: usize, text: &str) -> Error {
+            Error::Syntax {
+                index,
+                text: text.to_owned(),
+            }
+        }
+
+        from_str_ok!("", Ok(Vec::<Range<usize>>::new()));
+        from_str_ok!("  ", Ok(Vec::<Range<usize>>::new()));
+        from_str_ok!("0..5", Ok(vec![0..5]));
+        from_str_ok!(" 0..5 ,\t6..11 ", Ok(vec![0..5, 6..11]));
+        from_str_ok!("0..5,5..11", Ok(vec![0..5, 5..11]));
+        from_str_ok!("0..0,3..3", Ok(vec![0..0, 3..3]));
+        from_str_ok!("0..5,", Err(syntax(1, "")));
+        from_str_ok!("0..5,,6..8", Err(syntax(1, "")));
+        from_str_ok!("5", Err(syntax(0, "5")));
+        from_str_ok!("0..5,6..=8", Err(syntax(1, "6..=8")));
+        from_str_ok!("..5", Err(syntax(0, "..5")));
+        from_str_ok!("0 .. 5", Err(syntax(0, "0 .. 5")));
+        from_str_ok!("-1..5", Err(syntax(0, "-1..5")));
+        from_str_ok!(
+            "0..99999999999999999999999",
+            Err(syntax(0, "0..99999999999999999999999"))
+        );
+        from_str_ok!(
+            "0..1,5..3",
+            Err(Error::Inverted {
+                index: 1,
+                range: 5..3
+            })
+        );
     }
 
     #[test]
     fn from() {
         let ranges = Ranges::from(0..5);
         assert_eq!(ranges.ranges, [0..5], "from range");
+
+        macro_rules! from_ok {
+            ($input:expr, $expected:expr) => {
+                let input: Vec<Range<usize>> = $input;
+                let expected: &[Range<usize>] = &$expected;
+                assert_eq!(Ranges::from(input.clone()).ranges, expected, "from vec");
+                assert_eq!(
+                    Ranges::from(input.as_slice()).ranges,
+                    expected,
+                    "from slice"
+                );
+
+                let pairs: Ranges = input.iter().map(|r| (r.start, r.end)).collect();
+                assert_eq!(pairs.ranges, expected, "from pairs");
+            };
+        }
+
+        from_ok!(vec![], []);
+        from_ok!(vec![0..5], [0..5]);
+        from_ok!(vec![0..5, 5..11], [0..11]);
+        from_ok!(vec![0..2, 2..4, 4..6, 7..8, 8..9], [0..6, 7..9]);
+        from_ok!(vec![0..0, 0..5, 5..5, 5..8], [0..8]);
+        from_ok!(vec![3..3, 4..4], [3..3]);
+        from_ok!(vec![6..11, 5..6, 0..5, 3..1], [6..11, 5..6, 0..5, 3..1]);
+    }
+
+    #[test]
+    fn iter() {
+        let ranges: Ranges = [0..2, 2..5, 6..8].into_iter().collect();
+        assert_eq!(
+            ranges.iter().collect::<Vec<_>>(),
+            [&(0..5), &(6..8)],
+            "iter"
+        );
+
+        let mut borrowed = Vec::new();
+        for range in &ranges {
+            borrowed.push(range.clone());
+        }
+        assert_eq!(borrowed, [0..5, 6..8], "into_iter borrowed");
+
+        let owned: Vec<_> = ranges.into_iter().collect();
+        assert_eq!(owned, [0..5, 6..8], "into_iter owned");
+    }
+
+    #[test]
+    fn pairs() {
+        let ranges: Ranges = [6..11, 0..0, 0..5, 5..8].into_iter().collect();
+        let pairs = ranges.to_pairs();
+        assert_eq!(pairs, [[6, 11], [0, 8]]);
+        assert_eq!(Ranges::from_pairs(&pairs).ranges, ranges.ranges);
+
+        assert_eq!(Ranges::from_pairs(&[]).ranges, []);
+        assert_eq!(Ranges::from_pairs(&[[0, 2], [2, 5]]).ranges, [0..5]);
+    }
+
+    #[test]
+    fn bytes() {
+        macro_rules! bytes_ok {
+            ($input:expr, $expected:expr) => {
+                let mut ranges = Ranges::new();
+                ranges.ranges = $input;
+                let bytes = ranges.to_bytes();
+                assert_eq!(bytes, $expected, "to_bytes");
+                let decoded = Ranges::from_bytes(&bytes).expect("from_bytes");
+                assert_eq!(decoded.ranges, ranges.ranges, "from_bytes");
+            };
+        }
+
+        bytes_ok!(vec![], [0]);
+        bytes_ok!(vec![0..0], [1, 0, 0]);
+        bytes_ok!(vec![0..5, 6..11], [2, 0, 5, 2, 5]);
+        bytes_ok!(vec![6..11, 0..5], [2, 12, 5, 21, 5]);
+        #[cfg(target_pointer_width = "64")]
+        bytes_ok!(
+            vec![5..3],
+            [1, 10, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 1]
+        );
+        bytes_ok!(vec![200..300], [1, 0x90, 0x03, 100]);
+        bytes_ok!(vec![0..3, 3..5, 7..7], [3, 0, 3, 0, 2, 4, 0]);
+        bytes_ok!(vec![4..4, 0..0], [2, 8, 0, 7, 0]);
+
+        let ranges: Ranges = [usize::MAX..usize::MAX, 0..usize::MAX, 3..1]
+            .into_iter()
+            .collect();
+        let decoded = Ranges::from_bytes(&ranges.to_bytes()).expect("from_bytes");
+        assert_eq!(decoded.ranges, ranges.ranges, "extremes");
+    }
+
+    #[test]
+    fn from_bytes_invalid() {
+        macro_rules! invalid_ok {
+            ($input:expr, $offset:expr) => {
+                let input: &[u8] = &$input;
+                let got = Ranges::from_bytes(input).map(|r| r.ranges);
+                assert_eq!(got, Err(Error::Decode { offset: $offset }), "{:?}", input);
+            };
+        }
+
+        invalid_ok!([], 0);
+        invalid_ok!([1], 1);
+        invalid_ok!([1, 0], 2);
+        invalid_ok!([1, 0, 0x80], 3);
+        invalid_ok!([1, 0, 0, 0], 3);
+        invalid_ok!([0x80; 11], 0);
+        invalid_ok!(
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+            0
+        );
+    }
+
+    #[test]
+    fn narrow() {
+        let ranges: Ranges = [0..5, 5..8, 9..11, 3..1].into_iter().collect();
+        let narrow = ranges.try_narrow::<u32>().expect("try_narrow");
+        assert_eq!(narrow.ranges, [0..8, 9..11, 3..1]);
+        assert_eq!(narrow.widen().ranges, ranges.ranges);
+
+        let mut narrow = Ranges::<u16>::default();
+        narrow.push(0..2);
+        narrow.push(2..4);
+        narrow.extend([6..8, 8..9]);
+        assert_eq!(narrow.ranges, [0..4, 6..9]);
+        assert_eq!(narrow.to_string(), "0..4,6..9");
+
+        let ranges = Ranges::from(usize::MAX - 1..usize::MAX);
+        assert_eq!(
+            ranges.try_narrow::<u32>().map(|r| r.ranges),
+            Err(Error::Overflow {
+                index: 0,
+                range: usize::MAX - 1..usize::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn index() {
+        let ranges: Ranges = [0..2, 2..5, 6..8].into_iter().collect();
+        assert_eq!(ranges[0], 0..5, "index 0");
+        assert_eq!(ranges[1], 6..8, "index 1");
+    }
+
+    #[test]
+    fn accessors() {
+        let ranges = Ranges::new();
+        assert_eq!(ranges.first(), None, "first of empty");
+        assert_eq!(ranges.last(), None, "last of empty");
+        assert_eq!(ranges.get(0), None, "get of empty");
+
+        let ranges = Ranges::from(2..4);
+        assert_eq!(ranges.first(), Some(&(2..4)), "first of one");
+        assert_eq!(ranges.last(), Some(&(2..4)), "last of one");
+        assert_eq!(ranges.get(0), Some(&(2..4)), "get of one");
+    }
+
+    #[test]
+    fn contiguous() {
+        let strloin = Strloin::new("hello world");
+
+        macro_rules! contiguous_ok {
+            ($input:expr, $single:expr) => {
+                let ranges: Ranges = $input.into_iter().collect();
+                let borrowed = matches!(strloin.from_ranges_obj(&ranges), Borrowed(_));
+                assert_eq!(ranges.is_contiguous(), borrowed, "is_contiguous");
+                assert_eq!(ranges.as_single_range(), $single, "as_single_range");
+            };
+        }
+
+        contiguous_ok!(Vec::<Range<usize>>::new(), None);
+        contiguous_ok!([0..5], Some(&(0..5)));
+        contiguous_ok!([0..5, 5..11], Some(&(0..11)));
+        contiguous_ok!([0..5, 6..11], None);
+        contiguous_ok!([0..0, 0..5], Some(&(0..5)));
+        contiguous_ok!([0..5, 7..7, 5..11], Some(&(0..11)));
+        contiguous_ok!([3..3], Some(&(3..3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn index_out_of_bounds() {
+        let ranges: Ranges = [0..2, 2..5, 6..8].into_iter().collect();
+        let _ = &ranges[2];
     }
 
     #[test]
@@ -0,0 +1,154 @@
+use crate::cow::Cow;
+use crate::error::Error;
+use crate::ranges::Ranges;
+use crate::selection::Selection;
+use crate::strloin::Strloin;
+use std::ops::Range;
+
+/// A [`Ranges`] bound to a source string, which checks each range as it is pushed, so an
+/// invalid range is reported where it is created rather than when it is extracted.
+///
+/// Every range it holds can be sliced from the source string, so [`RangesIn::to_cow`] never
+/// panics. Construct one with [`Strloin::ranges_in`](crate::Strloin::ranges_in).
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Error, Strloin};
+///
+/// let strloin = Strloin::new("héllo world");
+/// let mut ranges = strloin.ranges_in();
+///
+/// assert_eq!(ranges.try_push(0..3), Ok(()));
+/// assert_eq!(ranges.try_push(3..6), Ok(()));
+/// assert_eq!(ranges.try_push(8..13), Err(Error::OutOfBounds { index: 1, range: 8..13, len: 12 }));
+/// assert_eq!(ranges.try_push(6..2), Err(Error::Inverted { index: 1, range: 6..2 }));
+/// assert_eq!(ranges.to_cow(), "héllo");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RangesIn<'a> {
+    source: &'a str,
+    ranges: Ranges,
+}
+
+impl<'a> RangesIn<'a> {
+    pub(crate) const fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            ranges: Ranges::new(),
+        }
+    }
+
+    /// Adds a new range, collapsing if possible as with [`Ranges::push`], after checking that it
+    /// can be sliced from the source string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Strloin::check_ranges`], in which case the range is not added.
+    pub fn try_push(&mut self, range: Range<usize>) -> Result<(), Error> {
+        Strloin::new(self.source).check_range(self.ranges.len(), &range)?;
+        self.ranges.push(range);
+        Ok(())
+    }
+
+    /// Adds a new range of `len` bytes starting at `start`, as with [`RangesIn::try_push`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LengthOverflow`] if `start + len` overflows `usize`, and otherwise the
+    /// errors of [`RangesIn::try_push`].
+    pub fn try_push_len(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        let end = start.checked_add(len).ok_or(Error::LengthOverflow {
+            index: self.ranges.len(),
+            start,
+            len,
+        })?;
+        self.try_push(start..end)
+    }
+
+    /// Returns the source string the ranges are checked against.
+    #[must_use]
+    pub const fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the ranges pushed so far.
+    #[must_use]
+    pub const fn ranges(&self) -> &Ranges {
+        &self.ranges
+    }
+
+    /// Converts this into the ranges pushed so far.
+    #[must_use]
+    pub fn into_ranges(self) -> Ranges {
+        self.ranges
+    }
+
+    /// Extracts the string for the ranges pushed so far, borrowing from the source string if
+    /// possible, as with [`Strloin::from_ranges_obj`].
+    #[must_use]
+    pub fn to_cow(&self) -> Cow<'a, str> {
+        Strloin::new(self.source).from_ranges_obj(&self.ranges)
+    }
+
+    /// Converts this into a [`Selection`] of its ranges.
+    #[must_use]
+    pub fn into_selection(self) -> Selection<'a> {
+        Selection::new(self.source, self.ranges)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::reversed_empty_ranges)]
+mod tests {
+    use crate::{Borrowed, Error, Strloin};
+
+    #[test]
+    fn try_push() {
+        let strloin = Strloin::new("héllo world");
+        let mut ranges = strloin.ranges_in();
+        assert!(matches!(ranges.to_cow(), Borrowed("")));
+
+        assert_eq!(ranges.try_push(7..12), Ok(()));
+        assert_eq!(ranges.try_push(6..7), Ok(()));
+        assert_eq!(ranges.try_push_len(0, 3), Ok(()));
+        assert_eq!(ranges.try_push(12..12), Ok(()));
+        assert_eq!(
+            ranges.try_push(0..2),
+            Err(Error::NotCharBoundary { index: 3, pos: 2 })
+        );
+        assert_eq!(
+            ranges.try_push(2..3),
+            Err(Error::NotCharBoundary { index: 3, pos: 2 })
+        );
+        assert_eq!(
+            ranges.try_push(5..4),
+            Err(Error::Inverted {
+                index: 3,
+                range: 5..4
+            })
+        );
+        assert_eq!(
+            ranges.try_push(13..13),
+            Err(Error::OutOfBounds {
+                index: 3,
+                range: 13..13,
+                len: 12
+            })
+        );
+        assert_eq!(
+            ranges.try_push_len(1, usize::MAX),
+            Err(Error::LengthOverflow {
+                index: 3,
+                start: 1,
+                len: usize::MAX
+            })
+        );
+
+        assert_eq!(ranges.source(), "héllo world");
+        assert_eq!(ranges.ranges().ranges, [7..12, 6..7, 0..3]);
+        assert_eq!(ranges.to_cow(), "world hé");
+        assert_eq!(ranges.clone().into_selection(), "world hé");
+        assert_eq!(ranges.into_ranges().len(), 3);
+    }
+}
@@ -0,0 +1,120 @@
+use std::io::{BufRead, Read, Result};
+use std::ops::Range;
+use std::slice::Iter;
+
+/// Streams the selected ranges of a source string as bytes, without concatenating them.
+///
+/// Construct one with [`Strloin::reader`](crate::Strloin::reader).
+#[derive(Debug, Clone)]
+pub struct Reader<'a, 'r> {
+    source: &'a str,
+    ranges: Iter<'r, Range<usize>>,
+    current: &'a [u8],
+}
+
+impl<'a, 'r> Reader<'a, 'r> {
+    pub(crate) fn new(source: &'a str, ranges: &'r [Range<usize>]) -> Self {
+        Reader {
+            source,
+            ranges: ranges.iter(),
+            current: &[],
+        }
+    }
+}
+
+impl Read for Reader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for Reader<'_, '_> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        while self.current.is_empty() {
+            let Some(range) = self.ranges.next() else {
+                break;
+            };
+            self.current = self.source[range.clone()].as_bytes();
+        }
+
+        Ok(self.current)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.current = &self.current[amt..];
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use crate::{Ranges, Strloin};
+    use std::io::{BufRead, Read};
+
+    #[test]
+    fn read_to_string() {
+        macro_rules! read_ok {
+            ($input:expr, $expected:expr) => {
+                let strloin = Strloin::new("hello world");
+                let ranges: Ranges = $input.into_iter().collect();
+
+                let mut got = String::new();
+                strloin
+                    .reader(&ranges)
+                    .read_to_string(&mut got)
+                    .expect("read_to_string");
+                assert_eq!(got, $expected, "read_to_string");
+            };
+        }
+
+        read_ok!(Vec::<std::ops::Range<usize>>::new(), "");
+        read_ok!(vec![0..5], "hello");
+        read_ok!(vec![0..5, 5..11], "hello world");
+        read_ok!(vec![0..5, 6..11], "helloworld");
+        read_ok!(vec![6..11, 5..6, 0..5], "world hello");
+        read_ok!(vec![0..0, 3..3, 0..5], "hello");
+    }
+
+    #[test]
+    fn small_buffer() {
+        let strloin = Strloin::new("hello world");
+        let ranges: Ranges = [0..5, 6..11].into_iter().collect();
+        let mut reader = strloin.reader(&ranges);
+
+        let mut buf = [0; 3];
+        assert_eq!(reader.read(&mut buf).expect("read"), 3);
+        assert_eq!(&buf, b"hel");
+        assert_eq!(reader.read(&mut buf).expect("read"), 2);
+        assert_eq!(&buf[..2], b"lo");
+        assert_eq!(reader.read(&mut buf).expect("read"), 3);
+        assert_eq!(&buf, b"wor");
+        assert_eq!(reader.read(&mut buf).expect("read"), 2);
+        assert_eq!(&buf[..2], b"ld");
+        assert_eq!(reader.read(&mut buf).expect("read"), 0);
+    }
+
+    #[test]
+    fn lines() {
+        let strloin = Strloin::new("one\ntwo\nthree\n");
+        let ranges: Ranges = [0..2, 4..6, 7..14].into_iter().collect();
+        let lines = strloin
+            .reader(&ranges)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("lines");
+        assert_eq!(lines, ["ontw", "three"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "when slicing")]
+    fn invalid_range() {
+        let strloin = Strloin::new("hello world");
+        let ranges = Ranges::from(2..1);
+        let mut got = String::new();
+        let _ = strloin.reader(&ranges).read_to_string(&mut got);
+    }
+}
@@ -0,0 +1,1930 @@
+use crate::cow::Cow;
+use crate::ranges::Ranges;
+use crate::strloin::Strloin;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{self, FlatMap};
+use std::ops::Range;
+use std::slice::Iter;
+use std::str::FromStr;
+
+/// A source string bundled with the [`Ranges`] selected from it. A `Selection` behaves like the
+/// string it would produce, without producing it until you ask with [`Selection::to_cow`].
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Strloin, Ranges};
+///
+/// let strloin = Strloin::new("hello world");
+/// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+///
+/// assert_eq!(selection, "helloworld");
+/// assert_eq!(selection.len(), 10);
+/// assert_eq!(selection.to_string(), "helloworld");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Selection<'a> {
+    pub source: &'a str,
+    pub ranges: Ranges,
+}
+
+impl<'a> Selection<'a> {
+    /// Construct a new [`Selection`] of the given ranges of the source string.
+    #[must_use]
+    pub const fn new(source: &'a str, ranges: Ranges) -> Self {
+        Selection { source, ranges }
+    }
+
+    /// Returns the [`Strloin`] for this selection's source string.
+    #[must_use]
+    pub const fn strloin(&self) -> Strloin<'a> {
+        Strloin::new(self.source)
+    }
+
+    /// Returns the length in bytes of the string this selection would produce.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.total_len()
+    }
+
+    /// Returns `true` if this selection would produce an empty string.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.ranges.iter().all(|r| r.start >= r.end)
+    }
+
+    /// Returns an iterator over the slice of the source string for each range, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select([6..11, 5..6, 0..5].into_iter().collect());
+    ///
+    /// assert_eq!(selection.segments().collect::<Vec<_>>(), ["world", " ", "hello"]);
+    /// ```
+    #[must_use]
+    pub fn segments(&self) -> Segments<'a, '_> {
+        Segments {
+            source: self.source,
+            ranges: self.ranges.ranges.iter(),
+        }
+    }
+
+    /// Returns an iterator over the characters of the string this selection would produce. It is
+    /// double-ended, so suffixes can be examined with `chars().rev()` without materializing the
+    /// selection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("file-v12.tar");
+    /// let selection = strloin.select([0..4, 5..8].into_iter().collect());
+    /// assert_eq!(selection, "filev12");
+    ///
+    /// let digits = selection.chars().rev().take_while(char::is_ascii_digit).count();
+    /// assert_eq!(digits, 2);
+    /// ```
+    #[must_use]
+    pub fn chars(&self) -> Chars<'a, '_> {
+        Chars {
+            chars: self.segments().flat_map(str::chars),
+        }
+    }
+
+    /// Returns an iterator over the characters of the string this selection would produce, each
+    /// with its byte offset in the source string. Unlike [`str::char_indices`], the offsets point
+    /// into the original document rather than the concatenation, for reporting where a character
+    /// came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("key: välue");
+    /// let selection = strloin.select([0..3, 5..11].into_iter().collect());
+    ///
+    /// let found = selection.char_indices().find(|&(_, c)| !c.is_ascii());
+    /// assert_eq!(found, Some((6, 'ä')));
+    /// ```
+    #[must_use]
+    pub fn char_indices(&self) -> CharIndices<'a, '_> {
+        CharIndices {
+            source: self.source,
+            ranges: self.ranges.ranges.iter(),
+            front: None,
+            back: None,
+        }
+    }
+
+    /// Extracts the selected string; if the ranges form a single contiguous region, then the
+    /// result will borrow from the source string. Otherwise, the ranges will be collected into an
+    /// owned string. See [`Strloin::from_ranges_obj`].
+    #[must_use]
+    pub fn to_cow(&self) -> Cow<'a, str> {
+        self.strloin().from_ranges_obj(&self.ranges)
+    }
+
+    /// Parses the selected string into another type, as with [`str::parse`]. If the ranges form
+    /// a single contiguous region, then the source string is parsed directly; otherwise the
+    /// ranges are first collected into an owned string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`FromStr::from_str`] if the selected string does not parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("id=1_024");
+    ///
+    /// let selection = strloin.select([3..4, 5..8].into_iter().collect());
+    /// assert_eq!(selection.parse::<u32>(), Ok(1024));
+    ///
+    /// let selection = strloin.select([0..2].into_iter().collect());
+    /// assert!(selection.parse::<u32>().is_err());
+    /// ```
+    pub fn parse<F: FromStr>(&self) -> Result<F, F::Err> {
+        self.to_cow().parse()
+    }
+
+    /// Returns the sub-selection for the given byte range of the string this selection would
+    /// produce, or `None` if the range is out of bounds or doesn't fall on character boundaries.
+    /// The sub-selection selects from the same source string, so calling `to_cow` on it will
+    /// borrow if the sub-range happens to be contiguous in the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("key: value!");
+    /// let selection = strloin.select([0..3, 4..10].into_iter().collect());
+    /// assert_eq!(selection, "key value");
+    ///
+    /// let field = selection.get(2..6).unwrap();
+    /// assert_eq!(field, "y va");
+    /// assert_eq!(field.ranges.ranges, [2..3, 4..7]);
+    ///
+    /// let value = selection.get(4..9).unwrap();
+    /// assert!(matches!(value.to_cow(), Borrowed("value")));
+    ///
+    /// assert!(selection.get(4..10).is_none());
+    /// ```
+    #[must_use]
+    pub fn get(&self, range: Range<usize>) -> Option<Self> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+
+        let start = self.advance(Position::default(), range.start);
+        let end = self.advance(start, range.end - range.start);
+        if !self.is_char_boundary(start) || !self.is_char_boundary(end) {
+            return None;
+        }
+
+        Some(Selection::new(self.source, self.ranges_between(start, end)))
+    }
+
+    /// Returns the sub-selection for the given byte range of the string this selection would
+    /// produce. See [`Selection::get`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't fall on character boundaries.
+    #[must_use]
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        self.get(range.clone()).unwrap_or_else(|| {
+            panic!(
+                "byte range {range:?} is out of bounds or not on a char boundary of selection `{self}`"
+            )
+        })
+    }
+
+    /// Translates a byte offset in the string this selection would produce into the byte offset
+    /// it came from in the source string, or `None` if it is out of bounds. An offset at the seam
+    /// between two ranges maps to the start of the later one, and the end of the output maps to
+    /// the end of the last range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("id=7; name=bob");
+    /// let selection = strloin.select([11..14, 0..2].into_iter().collect());
+    /// assert_eq!(selection, "bobid");
+    ///
+    /// let found = selection.find("id").unwrap();
+    /// assert_eq!(selection.map_output_offset(found), Some(0));
+    /// assert_eq!(selection.map_output_offset(1), Some(12));
+    /// assert_eq!(selection.map_output_offset(6), None);
+    /// ```
+    #[must_use]
+    pub fn map_output_offset(&self, offset: usize) -> Option<usize> {
+        if offset > self.len() {
+            return None;
+        }
+
+        let ranges = &self.ranges.ranges;
+        let mut position = self.advance(Position::default(), offset);
+        while position.offset == ranges.get(position.index)?.len() {
+            if position.index + 1 == ranges.len() {
+                break;
+            }
+            position.index += 1;
+            position.offset = 0;
+        }
+
+        Some(ranges[position.index].start + position.offset)
+    }
+
+    /// Translates a byte range of the string this selection would produce into the ranges of
+    /// the source string it came from, or `None` if it is out of bounds or doesn't fall on
+    /// character boundaries. This is the ranges of [`Selection::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("id=7; name=bob");
+    /// let selection = strloin.select([11..14, 0..2].into_iter().collect());
+    ///
+    /// let ranges = selection.map_output_range(2..4).unwrap();
+    /// assert_eq!(ranges.ranges, [13..14, 0..1]);
+    /// ```
+    #[must_use]
+    pub fn map_output_range(&self, range: Range<usize>) -> Option<Ranges> {
+        self.get(range).map(|selection| selection.ranges)
+    }
+
+    /// Returns a selection with leading and trailing whitespace removed, by shrinking the ranges
+    /// rather than building a new string. A trimmed contiguous selection still borrows.
+    ///
+    /// Whitespace is defined as in [`str::trim`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("  hello world  ");
+    /// let selection = strloin.select([0..5, 5..15].into_iter().collect());
+    ///
+    /// let trimmed = selection.trim();
+    /// assert_eq!(trimmed, "hello world");
+    /// assert!(matches!(trimmed.to_cow(), Borrowed("hello world")));
+    /// ```
+    #[must_use]
+    pub fn trim(&self) -> Self {
+        self.trim_start().trim_end()
+    }
+
+    /// Returns a selection with leading whitespace removed, by shrinking the ranges rather than
+    /// building a new string.
+    ///
+    /// Whitespace is defined as in [`str::trim_start`].
+    #[must_use]
+    pub fn trim_start(&self) -> Self {
+        let ranges = &self.ranges.ranges;
+        for (i, range) in ranges.iter().enumerate() {
+            let trimmed = self.source[range.clone()].trim_start();
+            if !trimmed.is_empty() {
+                let first = range.end - trimmed.len()..range.end;
+                return self.with_ranges(iter::once(first).chain(ranges[i + 1..].iter().cloned()));
+            }
+        }
+
+        self.with_ranges(None)
+    }
+
+    /// Returns a selection with trailing whitespace removed, by shrinking the ranges rather than
+    /// building a new string.
+    ///
+    /// Whitespace is defined as in [`str::trim_end`].
+    #[must_use]
+    pub fn trim_end(&self) -> Self {
+        let ranges = &self.ranges.ranges;
+        for (i, range) in ranges.iter().enumerate().rev() {
+            let trimmed = self.source[range.clone()].trim_end();
+            if !trimmed.is_empty() {
+                let last = range.start..range.start + trimmed.len();
+                return self.with_ranges(ranges[..i].iter().cloned().chain(iter::once(last)));
+            }
+        }
+
+        self.with_ranges(None)
+    }
+
+    /// Returns a selection with the given prefix removed, or `None` if the selection doesn't
+    /// start with it. The prefix may span any number of ranges; the comparison walks the ranges
+    /// directly, so neither side is materialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("GET /index.html");
+    /// let selection = strloin.select([0..2, 2..15].into_iter().collect());
+    ///
+    /// let path = selection.strip_prefix("GET ").unwrap();
+    /// assert_eq!(path, "/index.html");
+    /// assert!(matches!(path.to_cow(), Borrowed("/index.html")));
+    ///
+    /// assert!(selection.strip_prefix("POST ").is_none());
+    /// ```
+    #[must_use]
+    pub fn strip_prefix(&self, prefix: &str) -> Option<Self> {
+        let ranges = &self.ranges.ranges;
+        let (i, len) = self.match_prefix(prefix.as_bytes())?;
+        let Some(range) = ranges.get(i) else {
+            return Some(self.with_ranges(None));
+        };
+
+        let first = range.start + len..range.end;
+        Some(self.with_ranges(iter::once(first).chain(ranges[i + 1..].iter().cloned())))
+    }
+
+    /// Returns a selection with the given suffix removed, or `None` if the selection doesn't end
+    /// with it. The suffix may span any number of ranges; the comparison walks the ranges
+    /// directly, so neither side is materialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world\r\n");
+    /// let selection = strloin.select([0..5, 6..12, 12..13].into_iter().collect());
+    ///
+    /// assert_eq!(selection.strip_suffix("\r\n").unwrap(), "helloworld");
+    /// assert!(selection.strip_suffix("\n\n").is_none());
+    /// ```
+    #[must_use]
+    pub fn strip_suffix(&self, suffix: &str) -> Option<Self> {
+        let ranges = &self.ranges.ranges;
+        let (end, len) = self.match_suffix(suffix.as_bytes())?;
+        let Some(i) = end.checked_sub(1) else {
+            return Some(self.with_ranges(None));
+        };
+
+        let range = &ranges[i];
+        let last = range.start..range.end - len;
+        Some(self.with_ranges(ranges[..i].iter().cloned().chain(iter::once(last))))
+    }
+
+    /// Returns `true` if the selection starts with the given prefix, which may span any number
+    /// of ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+    ///
+    /// assert!(selection.starts_with("hellow"));
+    /// assert!(!selection.starts_with("hello "));
+    /// ```
+    #[must_use]
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.match_prefix(prefix.as_bytes()).is_some()
+    }
+
+    /// Returns `true` if the selection ends with the given suffix, which may span any number of
+    /// ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+    ///
+    /// assert!(selection.ends_with("loworld"));
+    /// assert!(!selection.ends_with("o world"));
+    /// ```
+    #[must_use]
+    pub fn ends_with(&self, suffix: &str) -> bool {
+        self.match_suffix(suffix.as_bytes()).is_some()
+    }
+
+    /// Finds where the given prefix ends, as the index of the range it ends in and how many bytes
+    /// of that range it covers. If the prefix covers every range, then the index is the number of
+    /// ranges.
+    fn match_prefix(&self, prefix: &[u8]) -> Option<(usize, usize)> {
+        let mut rest = prefix;
+        for (i, segment) in self.segments().enumerate() {
+            let segment = segment.as_bytes();
+            if rest.len() < segment.len() {
+                return segment.starts_with(rest).then_some((i, rest.len()));
+            }
+            rest = rest.strip_prefix(segment)?;
+        }
+
+        rest.is_empty().then_some((self.ranges.ranges.len(), 0))
+    }
+
+    /// Finds where the given suffix starts, as one past the index of the range it starts in and
+    /// how many bytes of that range it covers. If the suffix covers every range, then the index
+    /// is zero.
+    fn match_suffix(&self, suffix: &[u8]) -> Option<(usize, usize)> {
+        let mut rest = suffix;
+        for (i, segment) in self.segments().enumerate().rev() {
+            let segment = segment.as_bytes();
+            if rest.len() < segment.len() {
+                return segment.ends_with(rest).then(|| (i + 1, rest.len()));
+            }
+            rest = rest.strip_suffix(segment)?;
+        }
+
+        rest.is_empty().then_some((0, 0))
+    }
+
+    /// Returns the byte offset, in the string this selection would produce, of the first match
+    /// of the given needle. Matches may span any number of ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+    ///
+    /// assert_eq!(selection.find("low"), Some(3));
+    /// assert_eq!(selection.find("o w"), None);
+    /// ```
+    #[must_use]
+    pub fn find(&self, needle: &str) -> Option<usize> {
+        self.find_from(needle, Position::default())
+            .map(|position| position.pos)
+    }
+
+    /// Returns `true` if the given needle occurs in the selection. Matches may span any number of
+    /// ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+    ///
+    /// assert!(selection.contains("owo"));
+    /// assert!(!selection.contains("o w"));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, needle: &str) -> bool {
+        self.find(needle).is_some()
+    }
+
+    /// Returns the number of non-overlapping matches of the given needle, counted as
+    /// [`str::matches`] would on the string this selection would produce. Matches may span any
+    /// number of ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("banana split");
+    /// let selection = strloin.select([0..2, 4..6, 2..4].into_iter().collect());
+    /// assert_eq!(selection, "banana");
+    ///
+    /// assert_eq!(selection.count_matches("an"), 2);
+    /// assert_eq!(selection.count_matches("ana"), 1);
+    /// ```
+    #[must_use]
+    pub fn count_matches(&self, needle: &str) -> usize {
+        if needle.is_empty() {
+            return self.segments().map(|s| s.chars().count()).sum::<usize>() + 1;
+        }
+
+        let mut count = 0;
+        let mut from = Position::default();
+        while let Some(found) = self.find_from(needle, from) {
+            count += 1;
+            from = self.advance(found, needle.len());
+        }
+        count
+    }
+
+    /// Returns an iterator over the sub-selections separated by the given delimiter, which may
+    /// span any number of ranges. Each sub-selection selects from the same source string, so no
+    /// field is materialized until you ask for it. Follows the semantics of [`str::split`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("a,b;, c,d");
+    /// let selection = strloin.select([0..3, 4..9].into_iter().collect());
+    /// assert_eq!(selection, "a,b, c,d");
+    ///
+    /// let fields: Vec<_> = selection.split(", ").collect();
+    /// assert_eq!(fields, ["a,b", "c,d"]);
+    /// assert!(matches!(fields[0].to_cow(), Borrowed("a,b")));
+    /// ```
+    #[must_use]
+    pub fn split<'s, 'p>(&'s self, delimiter: &'p str) -> Split<'a, 's, 'p> {
+        Split {
+            selection: self,
+            delimiter,
+            start: Position::default(),
+            search: Some(Position::default()),
+            finished: false,
+        }
+    }
+
+    /// Returns `true` if the selection matches the given string, ignoring ASCII case. Compares
+    /// range by range, so neither side is materialized or lowercased.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("Content-Type: text/html");
+    /// let selection = strloin.select([0..7, 7..12].into_iter().collect());
+    ///
+    /// assert!(selection.eq_ignore_ascii_case("content-type"));
+    /// assert!(!selection.eq_ignore_ascii_case("content-length"));
+    /// ```
+    #[must_use]
+    pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+        eq_chunks(
+            self.segments().map(str::as_bytes),
+            iter::once(other.as_bytes()),
+            <[u8]>::eq_ignore_ascii_case,
+        )
+    }
+
+    /// Returns `true` if the selection matches the given string, ignoring case. Only ASCII letters
+    /// are compared case-insensitively, as with [`Selection::eq_ignore_ascii_case`]; enable the
+    /// `unicase` feature to compare with Unicode case folding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("Hello World");
+    /// let selection = strloin.select([0..5, 6..11].into_iter().collect());
+    ///
+    /// assert!(selection.eq_ignore_case("HELLOWORLD"));
+    /// ```
+    #[cfg(not(feature = "unicase"))]
+    #[must_use]
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.eq_ignore_ascii_case(other)
+    }
+
+    /// Returns `true` if the selection matches the given string, ignoring case. Both sides are
+    /// case folded character by character, walking the ranges so that neither side is
+    /// materialized, so `"ß"`, `"ẞ"` and `"SS"` are all equal. When both sides are ASCII, this is
+    /// the same as [`Selection::eq_ignore_ascii_case`], which is used as a fast path.
+    ///
+    /// The folding is built from the full case mappings of [`char`], with no table of its own: a
+    /// character is lowercased, uppercased and lowercased again. This agrees with Unicode full
+    /// case folding except that the Turkish dotless `"ı"` also folds to `"i"`, so it equals `"I"`
+    /// and `"i"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("ΣΊΣΥΦΟΣ");
+    /// let selection = strloin.select([0..6, 8..14].into_iter().collect());
+    ///
+    /// assert!(selection.eq_ignore_case("σίσφοσ"));
+    /// ```
+    #[cfg(feature = "unicase")]
+    #[must_use]
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        if other.is_ascii() && self.segments().all(str::is_ascii) {
+            return self.eq_ignore_ascii_case(other);
+        }
+
+        self.segments()
+            .flat_map(str::chars)
+            .flat_map(fold_case)
+            .eq(other.chars().flat_map(fold_case))
+    }
+
+    /// Finds the first match of the needle at or after the given position.
+    fn find_from(&self, needle: &str, from: Position) -> Option<Position> {
+        if needle.is_empty() {
+            return Some(from);
+        }
+
+        let mut base = from.pos - from.offset;
+        let mut offset = from.offset;
+
+        for (index, segment) in self.segments().enumerate().skip(from.index) {
+            if let Some(found) = segment[offset..].find(needle) {
+                let offset = offset + found;
+                return Some(Position {
+                    index,
+                    offset,
+                    pos: base + offset,
+                });
+            }
+
+            let seam = segment.len().saturating_sub(needle.len() - 1).max(offset);
+            for offset in seam..segment.len() {
+                if self.matches_at(index, offset, needle.as_bytes()) {
+                    return Some(Position {
+                        index,
+                        offset,
+                        pos: base + offset,
+                    });
+                }
+            }
+
+            base += segment.len();
+            offset = 0;
+        }
+
+        None
+    }
+
+    /// Returns `true` if the needle matches starting at the given offset into the given range.
+    fn matches_at(&self, index: usize, offset: usize, needle: &[u8]) -> bool {
+        let mut rest = needle;
+        let mut offset = offset;
+
+        for segment in self.segments().skip(index) {
+            let segment = &segment.as_bytes()[offset..];
+            if rest.len() <= segment.len() {
+                return segment.starts_with(rest);
+            }
+            match rest.strip_prefix(segment) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+            offset = 0;
+        }
+
+        false
+    }
+
+    /// Returns the position the given number of bytes after the given position.
+    fn advance(&self, from: Position, len: usize) -> Position {
+        let ranges = &self.ranges.ranges;
+        let mut position = from;
+        let mut rest = len;
+
+        while let Some(range) = ranges.get(position.index) {
+            let available = range.len() - position.offset;
+            if rest <= available {
+                position.offset += rest;
+                position.pos += rest;
+                break;
+            }
+
+            rest -= available;
+            position.pos += available;
+            position.index += 1;
+            position.offset = 0;
+        }
+
+        position
+    }
+
+    /// Returns the position after the character at the given position, if there is one.
+    fn next_char(&self, from: Position) -> Option<Position> {
+        let mut offset = from.offset;
+        for segment in self.segments().skip(from.index) {
+            if let Some(c) = segment[offset..].chars().next() {
+                return Some(self.advance(from, c.len_utf8()));
+            }
+            offset = 0;
+        }
+
+        None
+    }
+
+    /// Returns `true` if the given position falls on a character boundary of the source string.
+    fn is_char_boundary(&self, position: Position) -> bool {
+        self.ranges
+            .ranges
+            .get(position.index)
+            .map_or(true, |range| {
+                self.source.is_char_boundary(range.start + position.offset)
+            })
+    }
+
+    /// Returns the position at the end of the selection.
+    fn end(&self) -> Position {
+        Position {
+            index: self.ranges.ranges.len(),
+            offset: 0,
+            pos: self.len(),
+        }
+    }
+
+    /// Returns the ranges selecting everything between the two positions.
+    fn ranges_between(&self, from: Position, to: Position) -> Ranges {
+        let mut between = Ranges::new();
+        let ranges = self.ranges.ranges.iter().enumerate();
+        for (index, range) in ranges.take(to.index + 1).skip(from.index) {
+            let start = if index == from.index {
+                range.start + from.offset
+            } else {
+                range.start
+            };
+            let end = if index == to.index {
+                range.start + to.offset
+            } else {
+                range.end
+            };
+
+            if start < end {
+                between.push(start..end);
+            }
+        }
+
+        between
+    }
+
+    /// Returns a selection of the same source with the given ranges.
+    fn with_ranges<I: IntoIterator<Item = Range<usize>>>(&self, ranges: I) -> Self {
+        Selection::new(self.source, ranges.into_iter().collect())
+    }
+}
+
+impl fmt::Display for Selection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in self.segments() {
+            f.write_str(segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<str> for Selection<'_> {
+    fn eq(&self, other: &str) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        let mut rest = other.as_bytes();
+        for segment in self.segments() {
+            match rest.strip_prefix(segment.as_bytes()) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        }
+        rest.is_empty()
+    }
+}
+
+impl PartialEq<&str> for Selection<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Selection<'_>> for str {
+    fn eq(&self, other: &Selection<'_>) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<Selection<'_>> for &str {
+    fn eq(&self, other: &Selection<'_>) -> bool {
+        *other == **self
+    }
+}
+
+impl PartialEq<String> for Selection<'_> {
+    fn eq(&self, other: &String) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Selection<'_>> for String {
+    fn eq(&self, other: &Selection<'_>) -> bool {
+        *other == **self
+    }
+}
+
+/// Compares the strings two selections would produce, which may come from different sources,
+/// segment by segment without allocating either. Selections of different lengths are rejected
+/// without looking at their contents.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::Strloin;
+///
+/// let a = Strloin::new("hello world").select([0..5, 6..11].into_iter().collect());
+/// let b = Strloin::new("hell oworld").select([0..4, 5..11].into_iter().collect());
+/// assert_eq!(a, b);
+/// ```
+impl PartialEq for Selection<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && eq_chunks(
+                self.segments().map(str::as_bytes),
+                other.segments().map(str::as_bytes),
+                <[u8]>::eq,
+            )
+    }
+}
+
+impl Eq for Selection<'_> {}
+
+/// Orders lexicographically by bytes, the same as the [`str`] this selection would produce.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::Strloin;
+///
+/// const KEYWORDS: &[&str] = &["else", "fn", "if", "let", "while"];
+///
+/// let strloin = Strloin::new("w-h-i-l-e");
+/// let selection: strloin::Selection = strloin.select(
+///     [0..1, 2..3, 4..5, 6..7, 8..9].into_iter().collect(),
+/// );
+///
+/// let found = KEYWORDS.binary_search_by(|keyword| {
+///     keyword.partial_cmp(&selection).unwrap()
+/// });
+/// assert_eq!(found, Ok(4));
+/// ```
+impl Ord for Selection<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_chunks(
+            self.segments().map(str::as_bytes),
+            other.segments().map(str::as_bytes),
+        )
+    }
+}
+
+impl PartialOrd for Selection<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialOrd<str> for Selection<'_> {
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        Some(cmp_chunks(
+            self.segments().map(str::as_bytes),
+            iter::once(other.as_bytes()),
+        ))
+    }
+}
+
+impl PartialOrd<&str> for Selection<'_> {
+    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
+        self.partial_cmp(*other)
+    }
+}
+
+impl PartialOrd<Selection<'_>> for str {
+    fn partial_cmp(&self, other: &Selection<'_>) -> Option<Ordering> {
+        other.partial_cmp(self).map(Ordering::reverse)
+    }
+}
+
+impl PartialOrd<Selection<'_>> for &str {
+    fn partial_cmp(&self, other: &Selection<'_>) -> Option<Ordering> {
+        other.partial_cmp(*self).map(Ordering::reverse)
+    }
+}
+
+/// Case folds a character for [`Selection::eq_ignore_case`], by lowercasing, uppercasing and
+/// lowercasing again, so that `'ß'`, `'ẞ'` and `"SS"` all fold to `"ss"`.
+#[cfg(feature = "unicase")]
+fn fold_case(c: char) -> impl Iterator<Item = char> {
+    c.to_lowercase()
+        .flat_map(char::to_uppercase)
+        .flat_map(char::to_lowercase)
+}
+
+/// Tests two sequences of byte chunks for equality, as if each were concatenated, using the given
+/// function to compare pieces of equal length.
+fn eq_chunks<'x, 'y, X, Y, F>(mut xs: X, mut ys: Y, eq: F) -> bool
+where
+    X: Iterator<Item = &'x [u8]>,
+    Y: Iterator<Item = &'y [u8]>,
+    F: Fn(&[u8], &[u8]) -> bool,
+{
+    let mut x: &[u8] = &[];
+    let mut y: &[u8] = &[];
+
+    loop {
+        while x.is_empty() {
+            match xs.next() {
+                Some(chunk) => x = chunk,
+                None => break,
+            }
+        }
+        while y.is_empty() {
+            match ys.next() {
+                Some(chunk) => y = chunk,
+                None => break,
+            }
+        }
+
+        if x.is_empty() || y.is_empty() {
+            return x.is_empty() && y.is_empty();
+        }
+
+        let len = x.len().min(y.len());
+        if !eq(&x[..len], &y[..len]) {
+            return false;
+        }
+        x = &x[len..];
+        y = &y[len..];
+    }
+}
+
+/// Compares two sequences of byte chunks lexicographically, as if each were concatenated.
+fn cmp_chunks<'x, 'y, X, Y>(mut xs: X, mut ys: Y) -> Ordering
+where
+    X: Iterator<Item = &'x [u8]>,
+    Y: Iterator<Item = &'y [u8]>,
+{
+    let mut x: &[u8] = &[];
+    let mut y: &[u8] = &[];
+
+    loop {
+        while x.is_empty() {
+            match xs.next() {
+                Some(chunk) => x = chunk,
+                None => break,
+            }
+        }
+        while y.is_empty() {
+            match ys.next() {
+                Some(chunk) => y = chunk,
+                None => break,
+            }
+        }
+
+        if x.is_empty() || y.is_empty() {
+            return x.len().cmp(&y.len());
+        }
+
+        let len = x.len().min(y.len());
+        match x[..len].cmp(&y[..len]) {
+            Ordering::Equal => {
+                x = &x[len..];
+                y = &y[len..];
+            }
+            ordering => return ordering,
+        }
+    }
+}
+
+/// Hashes the same as the [`str`] (or [`String`]) this selection would produce, so it agrees
+/// with the [`PartialEq<String>`] impl. Note that [`HashMap::get`](std::collections::HashMap::get)
+/// on a `String`-keyed map can't take a `Selection`, so looking one up there still means building
+/// the key with [`Selection::to_cow`].
+///
+/// Many fast hashers (`FxHasher`, `ahash`, `foldhash`) mix each [`Hasher::write`] call on its own,
+/// so the bytes are fed in a single write, just as `str` does. A single range is hashed straight
+/// from the source; the segments of a short discontiguous selection are gathered on the stack,
+/// and only longer ones allocate.
+impl Hash for Selection<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        const INLINE_LEN: usize = 128;
+
+        if self.ranges.len() < 2 {
+            state.write(self.segments().next().unwrap_or_default().as_bytes());
+        } else {
+            let len = self.len();
+            let mut inline = [0; INLINE_LEN];
+            let mut heap = Vec::new();
+            let buf = if len <= INLINE_LEN {
+                &mut inline[..len]
+            } else {
+                heap.resize(len, 0);
+                &mut heap[..]
+            };
+
+            let mut filled = 0;
+            for segment in self.segments() {
+                buf[filled..filled + segment.len()].copy_from_slice(segment.as_bytes());
+                filled += segment.len();
+            }
+            state.write(buf);
+        }
+        state.write_u8(0xff);
+    }
+}
+
+/// An iterator over the sub-selections of a [`Selection`] separated by a delimiter.
+///
+/// Construct one with [`Selection::split`].
+#[derive(Debug, Clone)]
+pub struct Split<'a, 's, 'p> {
+    selection: &'s Selection<'a>,
+    delimiter: &'p str,
+    start: Position,
+    search: Option<Position>,
+    finished: bool,
+}
+
+impl<'a> Iterator for Split<'a, '_, '_> {
+    type Item = Selection<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let selection = self.selection;
+        let found = self
+            .search
+            .and_then(|search| selection.find_from(self.delimiter, search));
+
+        let Some(found) = found else {
+            self.finished = true;
+            let ranges = selection.ranges_between(self.start, selection.end());
+            return Some(Selection::new(selection.source, ranges));
+        };
+
+        let ranges = selection.ranges_between(self.start, found);
+        self.start = selection.advance(found, self.delimiter.len());
+        self.search = if self.delimiter.is_empty() {
+            selection.next_char(self.start)
+        } else {
+            Some(self.start)
+        };
+
+        Some(Selection::new(selection.source, ranges))
+    }
+}
+
+/// An iterator over the characters of a [`Selection`].
+///
+/// Construct one with [`Selection::chars`].
+#[derive(Debug, Clone)]
+pub struct Chars<'a, 'r> {
+    chars: FlatMap<Segments<'a, 'r>, std::str::Chars<'a>, fn(&'a str) -> std::str::Chars<'a>>,
+}
+
+impl Iterator for Chars<'_, '_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chars.next()
+    }
+}
+
+impl DoubleEndedIterator for Chars<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chars.next_back()
+    }
+}
+
+/// An iterator over the characters of a [`Selection`] with their byte offsets in the source
+/// string.
+///
+/// Construct one with [`Selection::char_indices`].
+#[derive(Debug, Clone)]
+pub struct CharIndices<'a, 'r> {
+    source: &'a str,
+    ranges: Iter<'r, Range<usize>>,
+    front: Option<(usize, std::str::CharIndices<'a>)>,
+    back: Option<(usize, std::str::CharIndices<'a>)>,
+}
+
+impl<'a> CharIndices<'a, '_> {
+    fn segment(&self, range: &Range<usize>) -> (usize, std::str::CharIndices<'a>) {
+        (range.start, self.source[range.clone()].char_indices())
+    }
+}
+
+impl Iterator for CharIndices<'_, '_> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((start, chars)) = &mut self.front {
+                if let Some((i, c)) = chars.next() {
+                    return Some((*start + i, c));
+                }
+            }
+
+            let Some(range) = self.ranges.next() else {
+                let (start, chars) = self.back.as_mut()?;
+                return chars.next().map(|(i, c)| (*start + i, c));
+            };
+            self.front = Some(self.segment(range));
+        }
+    }
+}
+
+impl DoubleEndedIterator for CharIndices<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((start, chars)) = &mut self.back {
+                if let Some((i, c)) = chars.next_back() {
+                    return Some((*start + i, c));
+                }
+            }
+
+            let Some(range) = self.ranges.next_back() else {
+                let (start, chars) = self.front.as_mut()?;
+                return chars.next_back().map(|(i, c)| (*start + i, c));
+            };
+            self.back = Some(self.segment(range));
+        }
+    }
+}
+
+/// A location within a [`Selection`]: the index of a range, a byte offset into that range, and
+/// the corresponding byte offset into the string the selection would produce.
+#[derive(Debug, Default, Clone, Copy)]
+struct Position {
+    index: usize,
+    offset: usize,
+    pos: usize,
+}
+
+/// An iterator over the slice of the source string for each range of a [`Selection`].
+///
+/// Construct one with [`Selection::segments`].
+#[derive(Debug, Clone)]
+pub struct Segments<'a, 'r> {
+    source: &'a str,
+    ranges: Iter<'r, Range<usize>>,
+}
+
+impl<'a> Iterator for Segments<'a, '_> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ranges.next().map(|r| &self.source[r.clone()])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Segments<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ranges.next_back().map(|r| &self.source[r.clone()])
+    }
+}
+
+impl ExactSizeIterator for Segments<'_, '_> {}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+    use crate::cow::{Borrowed, Owned};
+    use std::collections::hash_map::DefaultHasher;
+
+    fn select<'a>(source: &'a str, ranges: &[Range<usize>]) -> Selection<'a> {
+        Selection::new(source, ranges.iter().cloned().collect())
+    }
+
+    fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A hasher that, like `FxHasher` or `foldhash`, mixes each write on its own, so splitting
+    /// the same bytes across several writes changes the hash.
+    #[derive(Default)]
+    struct PerWriteHasher {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl Hasher for PerWriteHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            self.writes.push(bytes.to_vec());
+        }
+
+        fn finish(&self) -> u64 {
+            hash_of(&self.writes)
+        }
+    }
+
+    fn per_write_hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+        let mut hasher = PerWriteHasher::default();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn selection() {
+        macro_rules! selection_ok {
+            ($input:expr, $expected:expr, $is_borrow:expr) => {
+                let selection = select("hello world", $input);
+                let expected: &str = $expected;
+
+                assert_eq!(selection.len(), expected.len(), "len");
+                assert_eq!(selection.is_empty(), expected.is_empty(), "is_empty");
+                assert_eq!(selection.to_string(), expected, "Display");
+                assert_eq!(selection, expected, "PartialEq<&str>");
+                assert_eq!(expected, selection, "&str: PartialEq<Selection>");
+                assert_eq!(&selection, expected, "PartialEq<str>");
+                assert_eq!(hash_of(&selection), hash_of(expected), "Hash");
+
+                let cow = selection.to_cow();
+                assert_eq!(cow, expected, "to_cow");
+                if $is_borrow {
+                    assert!(matches!(cow, Borrowed(_)), "expected borrow");
+                } else {
+                    assert!(matches!(cow, Owned(_)), "expected owned");
+                }
+            };
+        }
+
+        selection_ok!(&[], "", true);
+        selection_ok!(&[3..3], "", true);
+        selection_ok!(&[0..5], "hello", true);
+        selection_ok!(&[0..5, 5..11], "hello world", true);
+        selection_ok!(&[0..5, 6..11], "helloworld", false);
+        selection_ok!(&[6..11, 5..6, 0..5], "world hello", false);
+        selection_ok!(&[0..0, 4..4], "", true);
+        selection_ok!(&[0..5, 7..7, 5..11], "hello world", true);
+    }
+
+    #[test]
+    fn not_equal() {
+        let selection = select("hello world", &[0..5, 6..11]);
+        assert_ne!(selection, "hello");
+        assert_ne!(selection, "helloworld!");
+        assert_ne!(selection, "hellowor");
+        assert_ne!(selection, "");
+        assert_ne!(selection, select("hello world", &[0..5]));
+    }
+
+    #[test]
+    fn eq_selection() {
+        let a = select("hello world", &[0..5, 6..11]);
+        let b = select("hell oworld", &[0..4, 5..11]);
+        let c = select("helloworld", &[0..10]);
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert_eq!(hash_of(&a), hash_of(&c));
+
+        let d = select(
+            "h-e-l-l-o-w-o-r-l-d",
+            &[0..1, 2..3, 4..5, 6..7, 8..9, 10..19],
+        );
+        assert_ne!(a, d);
+        let d = select(
+            "h-e-l-l-o-w-o-r-l-d",
+            &[0..1, 2..3, 4..5, 6..7, 8..9, 10..11],
+        );
+        assert_ne!(a, d);
+        let d = select(
+            "h-e-l-l-o-w-o-r-l-d",
+            &[
+                0..1,
+                2..3,
+                4..5,
+                6..7,
+                8..9,
+                10..11,
+                12..13,
+                14..15,
+                16..17,
+                18..19,
+            ],
+        );
+        assert_eq!(a, d);
+        assert_ne!(a, select("hello world", &[0..5, 6..10, 0..1]));
+        assert_eq!(select("", &[]), select("abc", &[1..1, 2..2]));
+    }
+
+    #[test]
+    fn count_matches() {
+        macro_rules! count_matches_ok {
+            ($source:expr, $ranges:expr, $needle:expr) => {
+                let selection = select($source, &$ranges);
+                let expected = selection.to_string().matches($needle).count();
+                assert_eq!(selection.count_matches($needle), expected, "{:?}", $needle);
+            };
+        }
+
+        count_matches_ok!("hello world", [], "o");
+        count_matches_ok!("hello world", [], "");
+        count_matches_ok!("hello world", [0..5, 6..11], "");
+        count_matches_ok!("héllo", [0..1, 3..6], "");
+        count_matches_ok!("hello world", [0..5, 6..11], "o");
+        count_matches_ok!("hello world", [0..5, 6..11], "ow");
+        count_matches_ok!("hello world", [0..5, 6..11], "o w");
+        count_matches_ok!("aaaa", [0..1, 1..1, 2..3, 0..2], "aa");
+        count_matches_ok!("a-b-a-b", [0..1, 2..3, 4..5, 6..7], "abab");
+        count_matches_ok!("a-b-a-b", [0..1, 2..3, 4..5, 6..7], "ba");
+        count_matches_ok!("xyz", [0..1, 1..2, 2..3, 0..3], "zx");
+    }
+
+    #[test]
+    fn chars() {
+        let selection = select("héllo wörld", &[0..3, 3..4, 7..8, 8..13, 1..1]);
+        assert_eq!(selection.to_string(), "hélwörld");
+
+        assert_eq!(selection.chars().collect::<String>(), "hélwörld");
+        assert_eq!(selection.chars().rev().collect::<String>(), "dlröwléh");
+
+        let mut chars = selection.chars();
+        assert_eq!(chars.next(), Some('h'));
+        assert_eq!(chars.next_back(), Some('d'));
+        assert_eq!(chars.next_back(), Some('l'));
+        assert_eq!(chars.by_ref().collect::<String>(), "élwör");
+        assert_eq!((chars.next(), chars.next_back()), (None, None));
+
+        assert_eq!(select("abc", &[]).chars().next_back(), None);
+    }
+
+    #[test]
+    fn char_indices() {
+        let source = "héllo wörld";
+        let selection = select(source, &[7..13, 5..5, 0..3, 3..4]);
+        assert_eq!(selection.to_string(), "wörldhél");
+
+        let expected = [
+            (7, 'w'),
+            (8, 'ö'),
+            (10, 'r'),
+            (11, 'l'),
+            (12, 'd'),
+            (0, 'h'),
+            (1, 'é'),
+            (3, 'l'),
+        ];
+        let got: Vec<_> = selection.char_indices().collect();
+        assert_eq!(got, expected);
+        for (offset, c) in got {
+            assert_eq!(source[offset..].chars().next(), Some(c));
+        }
+
+        let mut reversed = expected;
+        reversed.reverse();
+        assert_eq!(selection.char_indices().rev().collect::<Vec<_>>(), reversed);
+
+        let mut chars = selection.char_indices();
+        assert_eq!(chars.next_back(), Some((3, 'l')));
+        assert_eq!(chars.next(), Some((7, 'w')));
+        assert_eq!(chars.next_back(), Some((1, 'é')));
+        assert_eq!(chars.by_ref().count(), 5);
+        assert_eq!((chars.next(), chars.next_back()), (None, None));
+
+        let single = select(source, &[0..3]);
+        let mut chars = single.char_indices();
+        assert_eq!(chars.next(), Some((0, 'h')));
+        assert_eq!(chars.next_back(), Some((1, 'é')));
+        assert_eq!((chars.next(), chars.next_back()), (None, None));
+    }
+
+    #[test]
+    fn map_output_offset() {
+        let selection = Selection::new(
+            "hello world",
+            Ranges {
+                ranges: vec![6..11, 5..5, 5..6, 0..5],
+            },
+        );
+        assert_eq!(selection.to_string(), "world hello");
+
+        let got: Vec<_> = (0..=12)
+            .map(|offset| selection.map_output_offset(offset))
+            .collect();
+        let expected: Vec<_> = [6, 7, 8, 9, 10, 5, 0, 1, 2, 3, 4, 5]
+            .iter()
+            .map(|&offset| Some(offset))
+            .chain(iter::once(None))
+            .collect();
+        assert_eq!(got, expected);
+
+        assert_eq!(select("hello", &[]).map_output_offset(0), None);
+        assert_eq!(select("hello", &[2..4]).map_output_offset(2), Some(4));
+
+        let ranges = selection.map_output_range(3..8).map(|r| r.ranges);
+        assert_eq!(ranges, Some(vec![9..11, 5..6, 0..2]));
+        assert!(selection.map_output_range(3..12).is_none());
+    }
+
+    #[test]
+    fn eq_string() {
+        let selection = select("hello world", &[0..5, 6..11]);
+        let key = String::from("helloworld");
+        assert_eq!(selection, key);
+        assert_eq!(key, selection);
+        assert_eq!(hash_of(&selection), hash_of(&key));
+        assert_ne!(selection, String::from("hello world"));
+    }
+
+    #[test]
+    fn hash_per_write() {
+        let selection = select("hello world", &[0..5, 6..11]);
+        assert_eq!(
+            per_write_hash_of(&selection),
+            per_write_hash_of("helloworld")
+        );
+
+        let selection = select("hello world", &[6..11]);
+        assert_eq!(per_write_hash_of(&selection), per_write_hash_of("world"));
+
+        let selection = select("hello world", &[]);
+        assert_eq!(per_write_hash_of(&selection), per_write_hash_of(""));
+
+        let source = "x".repeat(300);
+        let selection = select(&source, &[0..100, 101..300]);
+        assert_eq!(
+            per_write_hash_of(&selection),
+            per_write_hash_of(&source[1..])
+        );
+    }
+
+    #[test]
+    fn segments() {
+        let selection = select("hello world", &[6..11, 5..6, 0..5]);
+        assert_eq!(selection.segments().len(), 3);
+        assert_eq!(
+            selection.segments().rev().collect::<Vec<_>>(),
+            ["hello", " ", "world"]
+        );
+    }
+
+    #[test]
+    fn trim() {
+        macro_rules! trim_ok {
+            ($input:expr, $start:expr, $end:expr, $both:expr) => {
+                let selection = select(" \thello  world\n ", $input);
+
+                let trimmed = selection.trim_start();
+                assert_eq!(trimmed, $start, "trim_start");
+                let trimmed = selection.trim_end();
+                assert_eq!(trimmed, $end, "trim_end");
+                let trimmed = selection.trim();
+                assert_eq!(trimmed, $both, "trim");
+            };
+        }
+
+        trim_ok!(&[], "", "", "");
+        trim_ok!(&[0..2], "", "", "");
+        trim_ok!(
+            &[0..16],
+            "hello  world\n ",
+            " \thello  world",
+            "hello  world"
+        );
+        trim_ok!(
+            &[0..7, 7..16],
+            "hello  world\n ",
+            " \thello  world",
+            "hello  world"
+        );
+        trim_ok!(&[0..1, 1..2, 7..9], "", "", "");
+        trim_ok!(&[0..1, 2..4, 12..16], "held\n ", " held", "held");
+        trim_ok!(&[0..3, 7..8, 14..16], "h \n ", " \th", "h");
+        trim_ok!(&[7..9, 2..7], "hello", "  hello", "hello");
+    }
+
+    #[test]
+    fn trim_borrows() {
+        let selection = select("  hello  ", &[0..1, 1..9]);
+        assert!(matches!(selection.to_cow(), Borrowed(_)));
+        assert!(matches!(selection.trim().to_cow(), Borrowed("hello")));
+
+        let selection = select("  hello  ", &[0..3, 4..6, 6..9]);
+        assert!(matches!(selection.to_cow(), Owned(_)));
+        assert!(matches!(selection.trim().to_cow(), Owned(_)));
+
+        let selection = select("  hello  ", &[0..1, 7..8, 2..7, 8..9]);
+        assert!(matches!(selection.to_cow(), Owned(_)));
+        assert!(matches!(selection.trim().to_cow(), Borrowed("hello")));
+    }
+
+    #[test]
+    fn strip() {
+        macro_rules! strip_ok {
+            ($input:expr, $affix:expr, $prefix:expr, $suffix:expr) => {
+                let selection = select("hello world", $input);
+                let prefix: Option<&str> = $prefix;
+                let suffix: Option<&str> = $suffix;
+
+                let stripped = selection.strip_prefix($affix);
+                assert_eq!(
+                    stripped.as_ref().map(ToString::to_string).as_deref(),
+                    prefix,
+                    "strip_prefix"
+                );
+                assert_eq!(
+                    stripped.map(|s| s.len()),
+                    prefix.map(str::len),
+                    "strip_prefix len"
+                );
+
+                let stripped = selection.strip_suffix($affix);
+                assert_eq!(
+                    stripped.as_ref().map(ToString::to_string).as_deref(),
+                    suffix,
+                    "strip_suffix"
+                );
+                assert_eq!(
+                    stripped.map(|s| s.len()),
+                    suffix.map(str::len),
+                    "strip_suffix len"
+                );
+            };
+        }
+
+        strip_ok!(&[], "", Some(""), Some(""));
+        strip_ok!(&[], "h", None, None);
+        strip_ok!(&[0..5], "", Some("hello"), Some("hello"));
+        strip_ok!(&[0..5], "hello", Some(""), Some(""));
+        strip_ok!(&[0..5], "hello!", None, None);
+        strip_ok!(&[0..5], "he", Some("llo"), None);
+        strip_ok!(&[0..5], "lo", None, Some("hel"));
+        strip_ok!(&[0..5, 6..11], "hellow", Some("orld"), None);
+        strip_ok!(&[0..5, 6..11], "helloworld", Some(""), Some(""));
+        strip_ok!(&[0..5, 6..11], "loworld", None, Some("hel"));
+        strip_ok!(&[0..5, 6..11], "world", None, Some("hello"));
+        strip_ok!(&[0..5, 6..11], "hello", Some("world"), None);
+        strip_ok!(&[0..5, 6..11], "hello world", None, None);
+        strip_ok!(&[0..1, 1..1, 2..3, 4..5], "hl", Some("o"), None);
+        strip_ok!(&[0..1, 1..1, 2..3, 4..5], "lo", None, Some("h"));
+        strip_ok!(&[0..1, 2..2, 4..5], "ho", Some(""), Some(""));
+    }
+
+    #[test]
+    fn starts_ends_with() {
+        macro_rules! affix_ok {
+            ($input:expr, $affix:expr, $starts:expr, $ends:expr) => {
+                let selection = select("hello world", $input);
+                assert_eq!(selection.starts_with($affix), $starts, "starts_with");
+                assert_eq!(selection.ends_with($affix), $ends, "ends_with");
+            };
+        }
+
+        affix_ok!(&[], "", true, true);
+        affix_ok!(&[], "h", false, false);
+        affix_ok!(&[0..5], "hello", true, true);
+        affix_ok!(&[0..5], "hello!", false, false);
+        affix_ok!(&[0..5, 6..11], "hellow", true, false);
+        affix_ok!(&[0..5, 6..11], "oworld", false, true);
+        affix_ok!(&[0..5, 6..11], "hello ", false, false);
+        affix_ok!(&[0..5, 6..11], " world", false, false);
+        affix_ok!(&[0..5, 6..11], "helloworld", true, true);
+        affix_ok!(&[0..1, 3..3, 2..3, 4..5], "hl", true, false);
+        affix_ok!(&[0..1, 3..3, 2..3, 4..5], "lo", false, true);
+        affix_ok!(&[0..1, 3..3, 2..3, 4..5], "hlo", true, true);
+        affix_ok!(&[6..11, 5..6, 0..5], "world h", true, false);
+        affix_ok!(&[6..11, 5..6, 0..5], "d hello", false, true);
+    }
+
+    #[test]
+    fn find() {
+        macro_rules! find_ok {
+            ($input:expr, $needle:expr, $expected:expr) => {
+                let selection = select("hello world", $input);
+                let expected: Option<usize> = $expected;
+                assert_eq!(selection.find($needle), expected, "find");
+                assert_eq!(selection.contains($needle), expected.is_some(), "contains");
+                assert_eq!(
+                    selection.find($needle),
+                    selection.to_string().find($needle),
+                    "find agrees with str::find"
+                );
+            };
+        }
+
+        find_ok!(&[], "", Some(0));
+        find_ok!(&[], "h", None);
+        find_ok!(&[0..5], "", Some(0));
+        find_ok!(&[0..5], "l", Some(2));
+        find_ok!(&[0..5], "lo", Some(3));
+        find_ok!(&[0..5], "low", None);
+        find_ok!(&[0..5, 6..11], "low", Some(3));
+        find_ok!(&[0..5, 6..11], "ow", Some(4));
+        find_ok!(&[0..5, 6..11], "o", Some(4));
+        find_ok!(&[0..5, 6..11], "d", Some(9));
+        find_ok!(&[0..5, 6..11], "helloworld", Some(0));
+        find_ok!(&[0..5, 6..11], "helloworld!", None);
+        find_ok!(&[0..5, 6..11], "o w", None);
+        find_ok!(&[0..1, 3..3, 2..3, 4..5, 7..8], "hlo", Some(0));
+        find_ok!(&[0..1, 3..3, 2..3, 4..5, 7..8], "loo", Some(1));
+        find_ok!(&[0..1, 3..3, 2..3, 4..5, 7..8], "oo", Some(2));
+        find_ok!(&[0..1, 3..3, 2..3, 4..5, 7..8], "ooo", None);
+        find_ok!(&[2..4, 2..4, 2..4], "lll", Some(0));
+        find_ok!(&[2..5, 2..5, 2..5], "lol", Some(1));
+        find_ok!(&[2..4, 2..4, 2..4], "ll", Some(0));
+    }
+
+    #[test]
+    fn find_multibyte() {
+        let selection = select("ünïcödé", &[0..3, 5..9]);
+        assert_eq!(selection, "üncöd");
+        assert_eq!(selection.find("nc"), Some(2));
+        assert_eq!(selection.find("öd"), Some(4));
+        assert_eq!(selection.find("üncöd"), Some(0));
+        assert_eq!(selection.find("ï"), None);
+    }
+
+    #[test]
+    fn split() {
+        macro_rules! split_ok {
+            ($source:expr, $input:expr, $delimiter:expr) => {
+                let selection = select($source, $input);
+                let got: Vec<_> = selection
+                    .split($delimiter)
+                    .map(|field| field.to_string())
+                    .collect();
+                let expected: Vec<_> = selection
+                    .to_string()
+                    .split($delimiter)
+                    .map(str::to_owned)
+                    .collect();
+                assert_eq!(got, expected, "split agrees with str::split");
+            };
+        }
+
+        split_ok!("a,b,c", &[], ",");
+        split_ok!("a,b,c", &[], "");
+        split_ok!("a,b,c", &[0..5], ",");
+        split_ok!("a,b,c", &[0..5], "");
+        split_ok!("a,b,c", &[0..5], ";");
+        split_ok!("a,b,c", &[0..2, 4..5], ",");
+        split_ok!("a,b,c", &[0..1, 2..3, 4..5], "");
+        split_ok!("a,b,c", &[0..1, 0..0, 2..3, 4..5], "");
+        split_ok!("a,b,c", &[1..2, 1..2, 1..2], ",");
+        split_ok!("a,b,c", &[1..2, 1..2, 1..2], ",,");
+        split_ok!("a,b,c", &[0..2, 4..5, 1..5], ",c,");
+        split_ok!("a, b,c", &[0..2, 2..3, 3..6], ", ");
+        split_ok!("a, b,c", &[0..2, 3..6], ",b");
+        split_ok!("aaaa", &[0..1, 1..2, 2..3, 3..4], "aa");
+        split_ok!("ünïcödé", &[0..3, 5..11], "");
+        split_ok!("ünïcödé", &[0..3, 5..11], "nc");
+    }
+
+    #[test]
+    fn split_borrows() {
+        let selection = select("a, b, c", &[0..3, 3..4, 4..7]);
+        let fields: Vec<_> = selection.split(", ").collect();
+        assert_eq!(fields, ["a", "b", "c"]);
+        for field in fields {
+            assert!(matches!(field.to_cow(), Borrowed(_)));
+        }
+
+        let selection = select("a, b, c", &[0..1, 2..7]);
+        let fields: Vec<_> = selection.split("b").collect();
+        assert_eq!(fields, ["a ", ", c"]);
+        assert!(matches!(fields[0].to_cow(), Owned(_)));
+        assert!(matches!(fields[1].to_cow(), Borrowed(_)));
+    }
+
+    #[test]
+    fn ordering() {
+        macro_rules! cmp_ok {
+            ($input:expr, $other:expr) => {
+                let selection = select("hello world", $input);
+                let string = selection.to_string();
+                let other: &str = $other;
+                let expected = string.as_str().cmp(other);
+
+                assert_eq!(
+                    selection.partial_cmp(other),
+                    Some(expected),
+                    "Selection vs str"
+                );
+                assert_eq!(
+                    other.partial_cmp(&selection),
+                    Some(expected.reverse()),
+                    "str vs Selection"
+                );
+
+                let split = other.len() / 2;
+                let other_selection = select(other, &[0..split, split..split, split..other.len()]);
+                assert_eq!(
+                    selection.cmp(&other_selection),
+                    expected,
+                    "Selection vs Selection"
+                );
+            };
+        }
+
+        cmp_ok!(&[], "");
+        cmp_ok!(&[], "a");
+        cmp_ok!(&[0..5], "hello");
+        cmp_ok!(&[0..5], "hell");
+        cmp_ok!(&[0..5], "hellp");
+        cmp_ok!(&[0..5], "helln");
+        cmp_ok!(&[0..5], "hello!");
+        cmp_ok!(&[0..5, 6..11], "helloworld");
+        cmp_ok!(&[0..5, 6..11], "hello world");
+        cmp_ok!(&[0..5, 6..11], "hellox");
+        cmp_ok!(&[0..5, 6..11], "hellowz");
+        cmp_ok!(&[0..1, 3..3, 2..3, 4..5], "hlo");
+        cmp_ok!(&[0..1, 3..3, 2..3, 4..5], "hl");
+        cmp_ok!(&[0..1, 3..3, 2..3, 4..5], "hlp");
+        cmp_ok!(&[0..1, 3..3, 2..3, 4..5], "");
+    }
+
+    #[test]
+    fn sort() {
+        let source = "banana apple cherry";
+        let mut selections = [
+            select(source, &[0..3, 5..6]),
+            select(source, &[7..12]),
+            select(source, &[13..19]),
+            select(source, &[0..1, 2..6]),
+        ];
+        selections.sort();
+        let sorted: Vec<_> = selections.iter().map(ToString::to_string).collect();
+        assert_eq!(sorted, ["apple", "bana", "bnana", "cherry"]);
+    }
+
+    #[test]
+    fn eq_ignore_ascii_case() {
+        let selection = select("Hello World", &[0..5, 6..11]);
+        assert!(selection.eq_ignore_ascii_case("helloworld"));
+        assert!(selection.eq_ignore_ascii_case("HELLOWORLD"));
+        assert!(selection.eq_ignore_ascii_case("HeLlOwOrLd"));
+        assert!(!selection.eq_ignore_ascii_case("hello world"));
+        assert!(!selection.eq_ignore_ascii_case("helloworl"));
+        assert!(!selection.eq_ignore_ascii_case("helloworlds"));
+        assert!(!selection.eq_ignore_ascii_case(""));
+
+        let selection = select("Straße", &[0..4, 4..4, 4..7]);
+        assert!(selection.eq_ignore_ascii_case("STRAßE"));
+        assert!(!selection.eq_ignore_ascii_case("STRASSE"));
+
+        let selection = select("", &[]);
+        assert!(selection.eq_ignore_ascii_case(""));
+        assert!(!selection.eq_ignore_ascii_case("a"));
+    }
+
+    #[cfg(not(feature = "unicase"))]
+    #[test]
+    fn eq_ignore_case() {
+        let selection = select("Hello World", &[0..5, 6..11]);
+        assert!(selection.eq_ignore_case("HELLOWORLD"));
+        assert!(!selection.eq_ignore_case("hello world"));
+
+        let selection = select("ÜBER ÄRGER", &[0..5, 6..12]);
+        assert!(selection.eq_ignore_case("ÜBERÄRGER"));
+        assert!(!selection.eq_ignore_case("überärger"));
+    }
+
+    #[cfg(feature = "unicase")]
+    #[test]
+    fn eq_ignore_case() {
+        let selection = select("Hello World", &[0..5, 6..11]);
+        assert!(selection.eq_ignore_case("HELLOWORLD"));
+        assert!(!selection.eq_ignore_case("hello world"));
+
+        let selection = select("ÜBER ÄRGER", &[0..5, 6..12]);
+        assert_eq!(selection, "ÜBERÄRGER");
+        assert!(selection.eq_ignore_case("überärger"));
+        assert!(selection.eq_ignore_case("ÜberÄrger"));
+        assert!(!selection.eq_ignore_case("uberarger"));
+
+        let selection = select("\u{212a}elvin", &[0..3, 3..8]);
+        assert!(selection.eq_ignore_case("kelvin"));
+        assert!(!selection.eq_ignore_ascii_case("kelvin"));
+
+        let selection = select("Straße", &[0..4, 4..7]);
+        assert!(selection.eq_ignore_case("STRAßE"));
+        assert!(selection.eq_ignore_case("STRASSE"));
+        assert!(selection.eq_ignore_case("STRAẞE"));
+        assert!(!selection.eq_ignore_case("STRASE"));
+
+        let selection = select("STRASSE", &[0..5, 5..7]);
+        assert!(selection.eq_ignore_case("straße"));
+
+        let selection = select("ΣΊΣΥΦΟΣ", &[0..6, 8..14]);
+        assert!(selection.eq_ignore_case("σίσφος"));
+        assert!(selection.eq_ignore_case("σίσφοσ"));
+        assert!(select("ı", &[0..2]).eq_ignore_case("I"));
+    }
+
+    #[test]
+    fn parse() {
+        let selection = select("12 34.5", &[0..1, 1..2]);
+        assert_eq!(selection.parse::<u8>(), Ok(12));
+
+        let selection = select("12 34.5", &[0..2, 3..5]);
+        assert_eq!(selection.parse::<u16>(), Ok(1234));
+        assert_eq!(selection.parse::<u8>().map_err(|_| ()), Err(()));
+
+        let selection = select("12 34.5", &[3..7]);
+        assert_eq!(selection.parse::<f64>(), Ok(34.5));
+        assert!(selection.parse::<i32>().is_err());
+
+        let selection = select("12 34.5", &[]);
+        assert!(selection.parse::<i32>().is_err());
+        assert_eq!(selection.parse::<String>().as_deref(), Ok(""));
+    }
+
+    #[test]
+    fn get() {
+        macro_rules! get_ok {
+            ($input:expr, $range:expr, $expected:expr) => {
+                let selection = select("hello world", $input);
+                let expected: Option<&str> = $expected;
+                let got = selection.get($range);
+                assert_eq!(
+                    got.as_ref().map(ToString::to_string).as_deref(),
+                    expected,
+                    "get"
+                );
+                assert_eq!(
+                    expected,
+                    selection.to_string().get($range),
+                    "get agrees with str::get"
+                );
+            };
+        }
+
+        get_ok!(&[], 0..0, Some(""));
+        get_ok!(&[], 0..1, None);
+        get_ok!(&[0..5], 0..5, Some("hello"));
+        get_ok!(&[0..5], 1..4, Some("ell"));
+        get_ok!(&[0..5], 5..5, Some(""));
+        get_ok!(&[0..5], 4..6, None);
+        get_ok!(&[0..5], 6..6, None);
+        get_ok!(&[0..5, 6..11], 3..7, Some("lowo"));
+        get_ok!(&[0..5, 6..11], 5..10, Some("world"));
+        get_ok!(&[0..5, 6..11], 0..5, Some("hello"));
+        get_ok!(&[0..5, 6..11], 5..5, Some(""));
+        get_ok!(&[0..5, 6..11], 0..10, Some("helloworld"));
+        get_ok!(&[0..1, 3..3, 2..3, 4..5], 1..3, Some("lo"));
+        get_ok!(&[0..1, 3..3, 2..3, 4..5], 0..2, Some("hl"));
+        get_ok!(&[6..11, 5..6, 0..5], 3..8, Some("ld he"));
+    }
+
+    #[test]
+    fn get_multibyte() {
+        let selection = select("ünïcödé", &[0..3, 5..9]);
+        assert_eq!(selection, "üncöd");
+        assert_eq!(
+            selection.get(0..2).map(|s| s.to_string()).as_deref(),
+            Some("ü")
+        );
+        assert_eq!(
+            selection.get(2..6).map(|s| s.to_string()).as_deref(),
+            Some("ncö")
+        );
+        assert!(selection.get(1..3).is_none());
+        assert!(selection.get(2..5).is_none());
+    }
+
+    #[test]
+    fn get_ranges() {
+        let selection = select("hello world", &[0..5, 6..11]);
+        assert_eq!(selection.slice(2..4).ranges.ranges, [2..4]);
+        assert_eq!(selection.slice(3..7).ranges.ranges, [3..5, 6..8]);
+        assert_eq!(selection.slice(5..7).ranges.ranges, [6..8]);
+        assert_eq!(selection.slice(3..5).ranges.ranges, [3..5]);
+        assert!(selection.slice(5..5).ranges.ranges.is_empty());
+        assert!(matches!(selection.slice(6..9).to_cow(), Borrowed("orl")));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn slice_out_of_bounds() {
+        let selection = select("hello world", &[0..5, 6..11]);
+        let _ = selection.slice(3..11);
+    }
+
+    #[test]
+    fn strip_borrows() {
+        let selection = select("hello world", &[0..2, 2..5, 6..11]);
+        let stripped = selection.strip_prefix("hellow").expect("strip_prefix");
+        assert!(matches!(stripped.to_cow(), Borrowed("orld")));
+        let stripped = selection.strip_suffix("oworld").expect("strip_suffix");
+        assert!(matches!(stripped.to_cow(), Borrowed("hell")));
+    }
+
+    #[test]
+    #[should_panic(expected = "when slicing")]
+    fn invalid_range() {
+        let selection = select("hello world", &[2..1]);
+        let _ = selection.to_string();
+    }
+}
@@ -0,0 +1,267 @@
+use crate::array_ranges::ArrayRanges;
+use crate::error::Error;
+use crate::ranges::{merge_into, Ranges};
+use std::ops::{Index, Range};
+use std::slice::Iter;
+
+/// An alternative to [`Ranges`] that stores its first `N` ranges inline and only allocates once
+/// it needs more, so the common case of one or two ranges never touches the heap.
+///
+/// Unlike [`ArrayRanges`], pushing never fails: past `N` ranges, the ranges move into a `Vec`.
+/// Pass [`SmallRanges::as_slice`] to [`Strloin::from_ranges`](crate::Strloin::from_ranges) to
+/// extract from it.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, SmallRanges, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+/// let mut ranges = SmallRanges::<2>::new();
+///
+/// ranges.push(0..5);
+/// ranges.push(5..11);
+/// assert!(!ranges.spilled());
+/// assert!(matches!(strloin.from_ranges(ranges.as_slice()), Borrowed("hello world")));
+///
+/// ranges.push(0..1);
+/// ranges.push(2..3);
+/// assert!(ranges.spilled());
+/// assert_eq!(strloin.from_ranges(ranges.as_slice()), "hello worldhl");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmallRanges<const N: usize> {
+    storage: Storage<N>,
+}
+
+#[derive(Debug, Clone)]
+enum Storage<const N: usize> {
+    Inline(ArrayRanges<N>),
+    Heap(Vec<Range<usize>>),
+}
+
+impl<const N: usize> SmallRanges<N> {
+    /// Construct a new empty [`SmallRanges`]. This is a `const fn`, so it can initialize a
+    /// `const` or `static`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            storage: Storage::Inline(ArrayRanges::new()),
+        }
+    }
+
+    /// Adds a new range, collapsing it into the last range if possible, just like
+    /// [`Ranges::push`]. If the range can't be collapsed and all `N` inline slots are in use, the
+    /// ranges move to the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::SmallRanges;
+    ///
+    /// let mut ranges = SmallRanges::<1>::new();
+    /// ranges.push(0..5);
+    /// ranges.push(5..8);
+    /// assert!(!ranges.spilled());
+    ///
+    /// ranges.push(9..11);
+    /// assert!(ranges.spilled());
+    /// assert_eq!(ranges.as_slice(), [0..8, 9..11]);
+    /// ```
+    pub fn push(&mut self, range: Range<usize>) {
+        match &mut self.storage {
+            Storage::Inline(inline) => {
+                if let Err(Error::Full { range, .. }) = inline.try_push(range) {
+                    let mut heap = Vec::with_capacity(N.max(1) * 2);
+                    heap.extend_from_slice(inline.as_slice());
+                    heap.push(range);
+                    self.storage = Storage::Heap(heap);
+                }
+            }
+            Storage::Heap(heap) => {
+                if let Some(last) = heap.last_mut() {
+                    if merge_into(last, &range) {
+                        return;
+                    }
+                }
+                heap.push(range);
+            }
+        }
+    }
+
+    /// Returns `true` if the ranges have outgrown the `N` inline slots and moved to the heap.
+    #[must_use]
+    pub const fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Returns the number of ranges, after collapsing.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns `true` if there are no ranges.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// Returns the ranges as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[Range<usize>] {
+        match &self.storage {
+            Storage::Inline(inline) => inline.as_slice(),
+            Storage::Heap(heap) => heap,
+        }
+    }
+
+    /// Returns an iterator over the ranges.
+    pub fn iter(&self) -> Iter<'_, Range<usize>> {
+        self.as_slice().iter()
+    }
+
+    /// Removes the last range and returns it, or `None` if it is empty. Ranges that have moved to
+    /// the heap stay there.
+    pub fn pop(&mut self) -> Option<Range<usize>> {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.pop(),
+            Storage::Heap(heap) => heap.pop(),
+        }
+    }
+
+    /// Removes all ranges, keeping any heap allocation for reuse.
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.clear(),
+            Storage::Heap(heap) => heap.clear(),
+        }
+    }
+}
+
+impl<const N: usize> Default for SmallRanges<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AsRef<[Range<usize>]> for SmallRanges<N> {
+    fn as_ref(&self) -> &[Range<usize>] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> Index<usize> for SmallRanges<N> {
+    type Output = Range<usize>;
+
+    /// Returns the range at position `index`, after collapsing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'r, const N: usize> IntoIterator for &'r SmallRanges<N> {
+    type Item = &'r Range<usize>;
+    type IntoIter = Iter<'r, Range<usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const N: usize> Extend<Range<usize>> for SmallRanges<N> {
+    fn extend<T: IntoIterator<Item = Range<usize>>>(&mut self, iter: T) {
+        for range in iter {
+            self.push(range);
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<Range<usize>> for SmallRanges<N> {
+    fn from_iter<T: IntoIterator<Item = Range<usize>>>(iter: T) -> Self {
+        let mut ranges = Self::new();
+        ranges.extend(iter);
+        ranges
+    }
+}
+
+/// Converts into [`Ranges`], reusing the heap allocation if the ranges have spilled.
+impl<const N: usize> From<SmallRanges<N>> for Ranges {
+    fn from(small: SmallRanges<N>) -> Self {
+        let ranges = match small.storage {
+            Storage::Inline(inline) => inline.as_slice().to_vec(),
+            Storage::Heap(heap) => heap,
+        };
+        Self { ranges }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push() {
+        macro_rules! push_ok {
+            ($input:expr) => {
+                let input: &[Range<usize>] = &$input;
+                let expected: Ranges = input.iter().cloned().collect();
+
+                let ranges: SmallRanges<2> = input.iter().cloned().collect();
+                assert_eq!(ranges.as_slice(), expected.ranges.as_slice(), "{:?}", input);
+                assert_eq!(ranges.len(), expected.len());
+                assert_eq!(ranges.spilled(), expected.len() > 2);
+
+                let ranges: SmallRanges<0> = input.iter().cloned().collect();
+                assert_eq!(ranges.as_slice(), expected.ranges.as_slice(), "{:?}", input);
+                assert_eq!(Ranges::from(ranges).ranges, expected.ranges);
+            };
+        }
+
+        push_ok!([]);
+        push_ok!([0..5]);
+        push_ok!([0..5, 5..11]);
+        push_ok!([0..5, 6..11]);
+        push_ok!([0..0, 0..5, 5..5, 5..8]);
+        push_ok!([0..2, 3..5, 6..8, 8..9]);
+        push_ok!([6..11, 5..6, 0..5, 3..1]);
+    }
+
+    #[test]
+    fn const_new() {
+        const EMPTY: SmallRanges<4> = SmallRanges::new();
+        let mut ranges = EMPTY;
+        ranges.push(0..2);
+        assert_eq!(ranges.as_slice(), [0..2]);
+        assert!(EMPTY.is_empty());
+        assert!(!EMPTY.spilled());
+    }
+
+    #[test]
+    fn edit() {
+        let mut ranges = SmallRanges::<1>::default();
+        ranges.push(0..2);
+        ranges.push(3..5);
+        assert!(ranges.spilled());
+        assert_eq!(ranges[1], 3..5);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [&(0..2), &(3..5)]);
+
+        assert_eq!(ranges.pop(), Some(3..5));
+        assert_eq!(ranges.len(), 1);
+        assert!(ranges.spilled());
+        ranges.clear();
+        assert_eq!(ranges.pop(), None);
+        assert!(ranges.is_empty());
+
+        let mut ranges: SmallRanges<2> = [0..2, 3..5].into_iter().collect();
+        assert_eq!(ranges.pop(), Some(3..5));
+        ranges.clear();
+        assert!(ranges.is_empty());
+        assert_eq!(Ranges::from(ranges).ranges, []);
+    }
+}
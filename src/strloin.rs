@@ -1,6 +1,31 @@
+use crate::annotate::Annotated;
+use crate::batch::Batch;
+use crate::builder::SelectionBuilder;
 use crate::cow::{Borrowed, Cow, Owned};
-use crate::ranges::{collapse_ranges, Ranges};
+use crate::cursor::Cursor;
+use crate::diff;
+use crate::error::Error;
+use crate::index::{widen, RangeIndex};
+use crate::named_ranges::NamedRanges;
+use crate::offset::OffsetStrloin;
+use crate::packed_ranges::PackedRanges;
+use crate::policy::CollapsePolicy;
+use crate::pool::{PooledStrloin, StringPool};
+use crate::provenance::ProvenanceCow;
+use crate::ranges::{collapse_ranges, collapse_ranges_iter, merge_into, Ranges};
+use crate::ranges_in::RangesIn;
+use crate::reader::Reader;
+use crate::selection::Selection;
+use crate::template::Template;
+use crate::tokenizer::Tokenizer;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FusedIterator;
 use std::ops::Range;
+use std::str::MatchIndices;
+
+/// The number of bytes [`Strloin::gather_tiny`] copies at once for each short run of ranges.
+const WORD: usize = 8;
 
 /// Holds a source string for conditionally borrowing.
 #[derive(Debug, Clone)]
@@ -8,76 +33,1111 @@ pub struct Strloin<'a> {
     pub source: &'a str,
 }
 
-impl<'a> Strloin<'a> {
-    /// Construct a new Strloin from the given string.
+impl<'a> Strloin<'a> {
+    /// Construct a new Strloin from the given string.
+    #[must_use]
+    pub const fn new(source: &'a str) -> Self {
+        Strloin { source }
+    }
+
+    /// Extracts a string from the given ranges; if the ranges form a single contiguous region,
+    /// then the result will borrow from the source string. Otherwise, the ranges will be collected
+    /// into an owned string. The ranges are scanned once, copying only from the first
+    /// discontinuity onwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert_eq!(strloin.from_ranges(&[0..5]), "hello"); // borrowed
+    /// assert_eq!(strloin.from_ranges(&[0..5, 5..11]), "hello world"); // borrowed
+    /// assert_eq!(strloin.from_ranges(&[0..5, 6..11]), "helloworld"); // owned
+    /// ```
+    #[must_use]
+    pub fn from_ranges(&self, ranges: &[Range<usize>]) -> Cow<'a, str> {
+        let (head, index) = self.contiguous_prefix(ranges);
+        if index == ranges.len() {
+            return Borrowed(head);
+        }
+
+        Owned(self.concat(head, ranges[index..].iter().cloned()))
+    }
+
+    /// Extracts a string from the given ranges, as with [`Strloin::from_ranges`], but returns an
+    /// error rather than panicking if any range can't be sliced from the source string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error found by [`Strloin::check_ranges`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Strloin};
+    ///
+    /// let strloin = Strloin::new("héllo");
+    ///
+    /// assert_eq!(strloin.try_from_ranges(&[0..1, 3..6]).unwrap(), "hllo");
+    /// assert_eq!(
+    ///     strloin.try_from_ranges(&[0..2]).unwrap_err(),
+    ///     Error::NotCharBoundary { index: 0, pos: 2 },
+    /// );
+    /// assert_eq!(
+    ///     strloin.try_from_ranges(&[0..1, 3..8]).unwrap_err(),
+    ///     Error::OutOfBounds { index: 1, range: 3..8, len: 6 },
+    /// );
+    /// ```
+    pub fn try_from_ranges(&self, ranges: &[Range<usize>]) -> Result<Cow<'a, str>, Error> {
+        self.check_ranges(ranges)?;
+        Ok(self.from_ranges(ranges))
+    }
+
+    /// Checks that every range can be sliced from the source string, which is what
+    /// [`Strloin::from_ranges`] would otherwise panic on.
+    ///
+    /// # Errors
+    ///
+    /// Returns, for the first range that can't be sliced, [`Error::Inverted`] if it starts after
+    /// it ends, [`Error::OutOfBounds`] if it ends past the end of the source string, or
+    /// [`Error::NotCharBoundary`] if it starts or ends inside a character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert_eq!(strloin.check_ranges(&[0..5, 6..11]), Ok(()));
+    /// assert_eq!(
+    ///     strloin.check_ranges(&[0..5, 8..6]),
+    ///     Err(Error::Inverted { index: 1, range: 8..6 }),
+    /// );
+    /// ```
+    pub fn check_ranges(&self, ranges: &[Range<usize>]) -> Result<(), Error> {
+        for (index, range) in ranges.iter().enumerate() {
+            self.check_range(index, range)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks a single range as with [`Strloin::check_ranges`], reporting it at `index`.
+    pub(crate) fn check_range(&self, index: usize, range: &Range<usize>) -> Result<(), Error> {
+        if range.start > range.end {
+            return Err(Error::Inverted {
+                index,
+                range: range.clone(),
+            });
+        }
+        if range.end > self.source.len() {
+            return Err(Error::OutOfBounds {
+                index,
+                range: range.clone(),
+                len: self.source.len(),
+            });
+        }
+        for pos in [range.start, range.end] {
+            if !self.source.is_char_boundary(pos) {
+                return Err(Error::NotCharBoundary { index, pos });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extracts a string from ranges of a narrower [`RangeIndex`] type such as `u32`, as with
+    /// [`Strloin::from_ranges`], widening each range to `usize` as it goes rather than needing
+    /// them copied into a `Range<usize>` list first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let spans: &[std::ops::Range<u32>] = &[0..5, 5..11];
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert!(matches!(strloin.from_index_ranges(spans), Borrowed("hello world")));
+    /// assert_eq!(strloin.from_index_ranges(&[6_u16..11, 0..5]), "worldhello");
+    /// ```
+    #[must_use]
+    pub fn from_index_ranges<I: RangeIndex>(&self, ranges: &[Range<I>]) -> Cow<'a, str> {
+        let wide = ranges.iter().map(widen);
+        self.collapse(wide.clone()).map_or_else(
+            || Owned(self.concat("", wide)),
+            |range| Borrowed(&self.source[range]),
+        )
+    }
+
+    /// Copies the string for the given ranges into the start of `buf`, without allocating, and
+    /// returns the filled part of `buf` as a `&str`. Unlike [`Strloin::from_ranges`], this
+    /// copies even when the ranges are contiguous.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BufferTooSmall`], with the number of bytes needed, if the result doesn't
+    /// fit in `buf`. Nothing is copied in that case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range can't be sliced from the source string, as with
+    /// [`Strloin::from_ranges`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let mut buf = [0; 16];
+    ///
+    /// let got = strloin.from_ranges_to_buf(&[6..11, 5..6, 0..5], &mut buf);
+    /// assert_eq!(got, Ok("world hello"));
+    ///
+    /// let got = strloin.from_ranges_to_buf(&[0..5, 6..11], &mut buf[..4]);
+    /// assert_eq!(got, Err(Error::BufferTooSmall { needed: 10, len: 4 }));
+    /// ```
+    pub fn from_ranges_to_buf<'b>(
+        &self,
+        ranges: &[Range<usize>],
+        buf: &'b mut [u8],
+    ) -> Result<&'b str, Error> {
+        let needed = ranges.iter().map(ExactSizeIterator::len).sum::<usize>();
+        if needed > buf.len() {
+            return Err(Error::BufferTooSmall {
+                needed,
+                len: buf.len(),
+            });
+        }
+
+        let len = self.copy_into(ranges, buf);
+        Ok(Self::filled_str(&buf[..len]))
+    }
+
+    /// Extracts a string from the given ranges, as with [`Strloin::from_ranges`], but copies a
+    /// large owned result on several threads. Each thread gets a share of the ranges and the part
+    /// of a preallocated buffer where they land, found with a prefix sum of the range lengths.
+    /// Results smaller than a few megabytes per thread are copied on the calling thread.
+    ///
+    /// The copy uses scoped threads from the standard library, so the `parallel` feature doesn't
+    /// pull in any dependencies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range can't be sliced from the source string, as with
+    /// [`Strloin::from_ranges`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert!(matches!(strloin.par_from_ranges(&[0..5, 5..11]), Borrowed("hello world")));
+    /// assert_eq!(strloin.par_from_ranges(&[6..11, 5..6, 0..5]), "world hello");
+    /// ```
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn par_from_ranges(&self, ranges: &[Range<usize>]) -> Cow<'a, str> {
+        const MIN_THREAD_LEN: usize = 4 << 20;
+
+        let (head, index) = self.contiguous_prefix(ranges);
+        if index == ranges.len() {
+            return Borrowed(head);
+        }
+
+        let len = ranges.iter().map(ExactSizeIterator::len).sum::<usize>();
+        let threads = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(len / MIN_THREAD_LEN);
+        if threads < 2 {
+            return Owned(self.concat(head, ranges[index..].iter().cloned()));
+        }
+
+        Owned(self.par_concat(ranges, len, threads))
+    }
+
+    /// Concatenates the source slices for `ranges`, whose lengths sum to `len`, by splitting them
+    /// into `threads` groups of about the same number of bytes and copying each group on its own
+    /// scoped thread into its part of the result.
+    #[cfg(feature = "parallel")]
+    fn par_concat(&self, ranges: &[Range<usize>], len: usize, threads: usize) -> String {
+        let share = (len + threads - 1) / threads;
+        let mut bytes = vec![0; len];
+
+        std::thread::scope(|scope| {
+            let mut ranges = ranges;
+            let mut rest = &mut bytes[..];
+            while !ranges.is_empty() {
+                let mut count = 0;
+                let mut group_len = 0;
+                while count < ranges.len() && group_len < share {
+                    group_len += ranges[count].len();
+                    count += 1;
+                }
+
+                let (group, next) = ranges.split_at(count);
+                let (buf, tail) = std::mem::take(&mut rest).split_at_mut(group_len);
+                ranges = next;
+                rest = tail;
+                scope.spawn(move || self.copy_into(group, buf));
+            }
+        });
+
+        Self::filled_string(bytes)
+    }
+
+    /// Copies the source slices for `ranges` into the start of `buf`, which must be big enough to
+    /// hold them, and returns the number of bytes copied.
+    fn copy_into(&self, ranges: &[Range<usize>], buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        self.for_each_run(ranges.iter().cloned(), |_, segment| {
+            buf[len..len + segment.len()].copy_from_slice(segment.as_bytes());
+            len += segment.len();
+        });
+        len
+    }
+
+    /// Extracts a string from the given ranges, collapsing them as the given [`CollapsePolicy`]
+    /// allows; if the ranges collapse into a single contiguous region, then the result will borrow
+    /// from the source string. Otherwise, the collapsed ranges will be collected into an owned
+    /// string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error found by [`Strloin::check_ranges`].
+    ///
+    /// # Panics
+    ///
+    /// Panics instead of returning an error if the policy's `panic_on_invalid` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{CollapsePolicy, Error, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let policy = CollapsePolicy {
+    ///     merge_overlapping: true,
+    ///     ..CollapsePolicy::new()
+    /// };
+    ///
+    /// assert_eq!(strloin.from_ranges_with(&[0..5, 2..11], &policy).unwrap(), "hello world"); // borrowed
+    /// assert_eq!(strloin.from_ranges_with(&[0..3, 1..5, 6..11], &policy).unwrap(), "helloworld"); // owned
+    /// assert_eq!(
+    ///     strloin.from_ranges_with(&[0..5, 9..2], &policy).unwrap_err(),
+    ///     Error::Inverted { index: 1, range: 9..2 },
+    /// );
+    /// ```
+    pub fn from_ranges_with(
+        &self,
+        ranges: &[Range<usize>],
+        policy: &CollapsePolicy,
+    ) -> Result<Cow<'a, str>, Error> {
+        if let Err(error) = self.check_ranges(ranges) {
+            assert!(!policy.panic_on_invalid, "{}", error);
+            return Err(error);
+        }
+
+        if let Some(range) = policy.collapse(ranges)? {
+            return Ok(Borrowed(&self.source[range]));
+        }
+
+        let mut owned = String::new();
+        let mut iter = ranges.iter().cloned();
+        let Some(mut current) = iter.next() else {
+            return Ok(Owned(owned));
+        };
+
+        for range in iter {
+            if !policy.merge(&mut current, &range) {
+                owned.push_str(&self.source[std::mem::replace(&mut current, range)]);
+            }
+        }
+
+        owned.push_str(&self.source[current]);
+        Ok(Owned(owned))
+    }
+
+    /// Extracts a string from the given ranges in reverse order, last range first; if the reversed
+    /// ranges form a single contiguous region, then the result will borrow from the source
+    /// string. Otherwise, the ranges will be collected into an owned string. This suits ranges
+    /// that were found while scanning backwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert_eq!(strloin.from_ranges_rev(&[6..11, 5..6, 0..5]), "hello world"); // borrowed
+    /// assert_eq!(strloin.from_ranges_rev(&[6..11, 0..5]), "helloworld"); // owned
+    /// assert_eq!(strloin.from_ranges_rev(&[0..5, 6..11]), "worldhello"); // owned
+    /// ```
+    #[must_use]
+    pub fn from_ranges_rev(&self, ranges: &[Range<usize>]) -> Cow<'a, str> {
+        if let Some(range) = self.collapse(ranges.iter().rev().cloned()) {
+            return Borrowed(&self.source[range]);
+        }
+
+        Owned(self.concat("", ranges.iter().rev().cloned()))
+    }
+
+    /// Returns the borrowed string for the longest prefix of the ranges that forms a single
+    /// contiguous region, along with the number of ranges in that prefix, which is the index where
+    /// the discontinuity starts.
+    ///
+    /// This lets the borrowable head be streamed out directly while only the tail, `&ranges[index..]`,
+    /// is handled separately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let ranges = [0..2, 2..5, 5..11, 0..5];
+    ///
+    /// let (head, index) = strloin.contiguous_prefix(&ranges);
+    /// assert_eq!((head, index), ("hello world", 3));
+    /// assert_eq!(strloin.from_ranges(&ranges[index..]), "hello"); // borrowed
+    /// ```
+    #[must_use]
+    pub fn contiguous_prefix(&self, ranges: &[Range<usize>]) -> (&'a str, usize) {
+        let mut prefix = 0..0;
+        let mut len = 0;
+
+        for range in ranges {
+            self.check_empty(range);
+            if range.start > range.end || !merge_into(&mut prefix, range) {
+                break;
+            }
+            len += 1;
+        }
+
+        (&self.source[prefix], len)
+    }
+
+    /// Extracts a string from the given [`Ranges`] object; if the ranges form a single contiguous
+    /// region, then the result will borrow from the source string. Otherwise, the ranges will be
+    /// collected into an owned string. If you're incrementally building up the list of ranges and
+    /// checking each time, using `from_ranges_obj` is more efficient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Strloin, Ranges};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// let mut ranges = Ranges::from(0..5);
+    ///
+    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello"); // borrowed
+    ///
+    /// ranges.push(5..11);
+    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello world"); // borrowed
+    ///
+    /// ranges.push(5..11);
+    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello world world"); // owned
+    /// ```
+    #[must_use]
+    pub fn from_ranges_obj<I: RangeIndex>(&self, ranges: &Ranges<I>) -> Cow<'a, str> {
+        match ranges.ranges.as_slice() {
+            &[] => Borrowed(""),
+            [range] => Borrowed(&self.source[widen(range)]),
+            ranges => Owned(self.concat("", ranges.iter().map(widen))),
+        }
+    }
+
+    /// Extracts a string from the given [`Ranges`] as with [`Strloin::from_ranges_obj`], and
+    /// returns it as a [`ProvenanceCow`] bundled with the source string and the ranges, so the
+    /// text and where it came from can't drift apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let got = strloin.from_ranges_provenance([6..11].into_iter().collect());
+    ///
+    /// assert_eq!(got, "world");
+    /// assert!(got.is_borrowed());
+    /// assert_eq!(got.ranges().first(), Some(&(6..11)));
+    /// ```
+    #[must_use]
+    pub fn from_ranges_provenance(&self, ranges: Ranges) -> ProvenanceCow<'a> {
+        ProvenanceCow::new(self.from_ranges_obj(&ranges), self.source, ranges)
+    }
+
+    /// Extracts a string from each of the given [`Ranges`], as with [`Strloin::from_ranges_obj`],
+    /// but concatenating every owned result into one shared buffer, allocated once, instead of a
+    /// `String` each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Ranges, Strloin};
+    ///
+    /// let strloin = Strloin::new("a,b;c,d");
+    /// let fields: Vec<Ranges> = Ranges::from_split("a,b;c,d", ";")
+    ///     .iter()
+    ///     .map(|field| [field.start..field.start + 1, field.end - 1..field.end].into_iter().collect())
+    ///     .collect();
+    ///
+    /// let batch = strloin.from_ranges_batch(&fields);
+    /// assert_eq!(batch.iter().collect::<Vec<_>>(), ["ab", "cd"]);
+    /// ```
+    #[must_use]
+    pub fn from_ranges_batch(&self, batch: &[Ranges]) -> Batch<'a> {
+        let capacity = batch
+            .iter()
+            .filter(|ranges| !ranges.is_contiguous())
+            .map(Ranges::total_len)
+            .sum();
+
+        let mut results = Batch::with_capacity(capacity, batch.len());
+        for ranges in batch {
+            match ranges.ranges.as_slice() {
+                [] => results.push_borrowed(""),
+                [range] => results.push_borrowed(&self.source[range.clone()]),
+                ranges => results.push_owned(ranges.iter().map(|r| &self.source[r.clone()])),
+            }
+        }
+        results
+    }
+
+    /// Extracts a string for each key of the given [`NamedRanges`], as with
+    /// [`Strloin::from_ranges_obj`], so each result borrows from the source string when its
+    /// ranges form a single contiguous region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, NamedRanges, Owned, Strloin};
+    ///
+    /// let strloin = Strloin::new("2024-01-15");
+    /// let named: NamedRanges<_> = [("year", 0..4), ("date", 5..7), ("date", 8..10)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let got = strloin.from_named_ranges(&named);
+    /// assert!(matches!(got["year"], Borrowed("2024")));
+    /// assert!(matches!(&got["date"], Owned(date) if date == "0115"));
+    /// ```
+    #[must_use]
+    pub fn from_named_ranges<K>(&self, named: &NamedRanges<K>) -> HashMap<K, Cow<'a, str>>
+    where
+        K: Clone + Eq + Hash,
+    {
+        named
+            .iter()
+            .map(|(key, ranges)| (key.clone(), self.from_ranges_obj(ranges)))
+            .collect()
+    }
+
+    /// Extracts a string from the given [`PackedRanges`]; if there's at most one range after
+    /// collapsing, then the result will borrow from the source string. Otherwise, the ranges will
+    /// be concatenated into an owned string, allocated once at its final size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, PackedRanges, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// let mut ranges = PackedRanges::new();
+    /// ranges.push(0..5);
+    /// ranges.push(5..11);
+    /// assert!(matches!(strloin.from_packed_ranges(&ranges), Borrowed("hello world")));
+    ///
+    /// ranges.push(5..11);
+    /// assert_eq!(strloin.from_packed_ranges(&ranges), "hello world world"); // owned
+    /// ```
+    #[must_use]
+    pub fn from_packed_ranges(&self, ranges: &PackedRanges) -> Cow<'a, str> {
+        let mut iter = ranges.iter();
+        match (iter.next(), iter.len()) {
+            (None, _) => Borrowed(""),
+            (Some(range), 0) => Borrowed(&self.source[range]),
+            (Some(first), _) => {
+                let mut owned = String::with_capacity(ranges.total_len());
+                owned.push_str(&self.source[first]);
+                for range in iter {
+                    owned.push_str(&self.source[range]);
+                }
+                Owned(owned)
+            }
+        }
+    }
+
+    /// Extracts a string from the given ranges, passing the slice for each range through `f`
+    /// (for example, to unescape it). If the ranges form a single contiguous region and `f` returns
+    /// every slice unchanged, as `Borrowed` of the very slice it was given, then the result will
+    /// borrow from the source string. Otherwise, the mapped slices will be collected into an owned
+    /// string. A single range is returned exactly as `f` maps it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Cow, Owned, Strloin};
+    ///
+    /// fn unescape(s: &str) -> Cow<'_, str> {
+    ///     if s.contains("\\\"") {
+    ///         Owned(s.replace("\\\"", "\""))
+    ///     } else {
+    ///         Borrowed(s)
+    ///     }
+    /// }
+    ///
+    /// let strloin = Strloin::new(r#"say \"hi\" to them"#);
+    ///
+    /// let got = strloin.map_ranges(&[0..3, 3..4], unescape);
+    /// assert!(matches!(got, Borrowed("say ")));
+    ///
+    /// let got = strloin.map_ranges(&[0..4, 4..10], unescape);
+    /// assert!(matches!(got, Owned(_)));
+    /// assert_eq!(got, r#"say "hi""#);
+    /// ```
+    #[must_use]
+    pub fn map_ranges<F>(&self, ranges: &[Range<usize>], mut f: F) -> Cow<'a, str>
+    where
+        F: FnMut(&'a str) -> Cow<'a, str>,
+    {
+        if let [range] = ranges {
+            return f(&self.source[range.clone()]);
+        }
+
+        let contiguous = collapse_ranges(ranges);
+        let mut owned: Option<String> = None;
+        // End of the borrowed prefix so far; empty ranges don't move it.
+        let mut prefix_end = contiguous.as_ref().map_or(0, |range| range.start);
+
+        for range in ranges {
+            let segment = &self.source[range.clone()];
+            let mapped = f(segment);
+
+            if let Some(owned) = &mut owned {
+                owned.push_str(&mapped);
+                continue;
+            }
+
+            let unchanged = matches!(mapped, Borrowed(m) if std::ptr::eq(m, segment));
+            if unchanged && contiguous.is_some() {
+                if !range.is_empty() {
+                    prefix_end = range.end;
+                }
+                continue;
+            }
+
+            let mut buf = String::new();
+            if let Some(contiguous) = &contiguous {
+                buf.push_str(&self.source[contiguous.start..prefix_end]);
+            }
+            buf.push_str(&mapped);
+            owned = Some(buf);
+        }
+
+        match (owned, contiguous) {
+            (Some(owned), _) => Owned(owned),
+            (None, Some(range)) => Borrowed(&self.source[range]),
+            (None, None) => Owned(String::new()),
+        }
+    }
+
+    /// Returns an iterator over the range of each field of the source string separated by
+    /// `delimiter`, as found by [`str::split`], so the fields can be fed straight back into
+    /// [`Strloin::from_ranges`] or a [`Ranges`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("name,,age");
+    /// let fields: Vec<_> = strloin.split_ranges(",").collect();
+    /// assert_eq!(fields, [0..4, 5..5, 6..9]);
+    /// assert_eq!(strloin.from_ranges(&[fields[2].clone(), fields[0].clone()]), "agename");
+    /// ```
+    #[must_use]
+    pub fn split_ranges<'p>(&self, delimiter: &'p str) -> SplitRanges<'a, 'p> {
+        self.splitn_ranges(usize::MAX, delimiter)
+    }
+
+    /// Returns an iterator over the range of each of at most `n` fields of the source string
+    /// separated by `delimiter`, as found by [`str::splitn`]. The last field covers the rest of
+    /// the source string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("key=value=more");
+    /// let fields: Vec<_> = strloin.splitn_ranges(2, "=").collect();
+    /// assert_eq!(fields, [0..3, 4..14]);
+    /// ```
+    #[must_use]
+    pub fn splitn_ranges<'p>(&self, n: usize, delimiter: &'p str) -> SplitRanges<'a, 'p> {
+        SplitRanges {
+            matches: self.source.match_indices(delimiter),
+            start: 0,
+            end: self.source.len(),
+            remaining: n,
+        }
+    }
+
+    /// Returns an iterator over the range of each line of the source string, as found by
+    /// [`str::lines`]. Lines end with either `\n` or `\r\n`, which is not included in the
+    /// range, and a trailing empty line is not yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("one\r\ntwo\nthree\n");
+    /// let lines: Vec<_> = strloin.lines_ranges().collect();
+    /// assert_eq!(lines, [0..3, 5..8, 9..14]);
+    /// assert_eq!(strloin.from_ranges(&[lines[2].clone(), lines[0].clone()]), "threeone");
+    /// ```
+    #[must_use]
+    pub const fn lines_ranges(&self) -> LinesRanges<'a> {
+        LinesRanges {
+            source: self.source,
+            start: 0,
+        }
+    }
+
+    /// Returns a [`Tokenizer`] that splits the source string into tokens separated by runs of
+    /// any of `delimiters`, yielding each token and each delimiter run in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("let x = 1;");
+    /// let tokens = strloin.tokenize(&[' ', ';']).tokens();
+    /// assert_eq!(strloin.from_ranges_obj(&tokens), "letx=1");
+    /// ```
+    #[must_use]
+    pub const fn tokenize<'d>(&self, delimiters: &'d [char]) -> Tokenizer<'a, 'd> {
+        Tokenizer::new(self.source, delimiters)
+    }
+
+    /// Returns a [`Template`] that reproduces `target` from ranges of the source string, filling
+    /// in whatever isn't found there with literals borrowed from `target`. Building the template
+    /// borrows from the source string if `target` is a single contiguous region of it.
+    ///
+    /// Each piece is the longest match found in the source string, which makes it easy to move
+    /// code that builds a fresh `String` over to strloin. It scans the whole source string for
+    /// every piece, so it's meant for retrofitting rather than hot loops.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Borrowed, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// let template = strloin.diff("world, hello");
+    /// let ranges: Vec<_> = template.ranges().cloned().collect();
+    /// assert_eq!(ranges, [6..11, 5..6, 0..5]); // plus the literal ","
+    /// assert_eq!(template.build(), "world, hello");
+    ///
+    /// assert!(matches!(strloin.diff("lo wo").build(), Borrowed("lo wo")));
+    /// ```
+    #[must_use]
+    pub fn diff<'t>(&self, target: &'t str) -> Template<'a, 't> {
+        diff::diff(self.source, target)
+    }
+
+    /// Returns the range of the source string matching the longest prefix of `candidate`, or
+    /// `None` if even its first character isn't found. Ties go to the earliest match.
+    ///
+    /// This is the anchor matching that [`Strloin::diff`] is built on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// assert_eq!(strloin.longest_prefix_match("world peace"), Some(6..11));
+    /// assert_eq!(strloin.longest_prefix_match("peace"), None);
+    /// ```
+    #[must_use]
+    pub fn longest_prefix_match(&self, candidate: &str) -> Option<Range<usize>> {
+        diff::longest_match(self.source, candidate, 0)
+    }
+
+    /// Returns the longest substring common to the source string and `candidate`, as its range
+    /// in each, or `None` if they have no character in common. Ties go to the earliest match in
+    /// `candidate`, then in the source string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let (source, candidate) = strloin.longest_common_substring("say hello!").unwrap();
+    /// assert_eq!(candidate, 4..9);
+    /// assert_eq!(strloin.from_ranges(&[source]), "hello");
+    /// ```
+    #[must_use]
+    pub fn longest_common_substring(
+        &self,
+        candidate: &str,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        diff::longest_common_substring(self.source, candidate)
+    }
+
+    /// Returns an [`Annotated`] view of the source string that displays each line that `ranges`
+    /// select from, with carets marking the selected characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// assert_eq!(
+    ///     strloin.annotate(&[0..1, 6..11]).to_string(),
+    ///     "1 | hello world\n  | ^     ^^^^^\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn annotate<'r>(&self, ranges: &'r [Range<usize>]) -> Annotated<'a, 'r> {
+        Annotated::new(self.source, ranges)
+    }
+
+    /// Returns a [`Reader`] that streams the selected ranges, implementing [`std::io::Read`] and
+    /// [`std::io::BufRead`]. Each range is handed out directly from the source string, so
+    /// discontiguous selections are never concatenated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use strloin::{Strloin, Ranges};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let ranges: Ranges = [0..5, 6..11].into_iter().collect();
+    ///
+    /// let mut out = String::new();
+    /// strloin.reader(&ranges).read_to_string(&mut out).unwrap();
+    /// assert_eq!(out, "helloworld");
+    /// ```
+    #[must_use]
+    pub fn reader<'r>(&self, ranges: &'r Ranges) -> Reader<'a, 'r> {
+        Reader::new(self.source, &ranges.ranges)
+    }
+
+    /// Bundles the given [`Ranges`] with this source string into a [`Selection`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Strloin, Ranges};
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let selection = strloin.select(Ranges::from(0..5));
+    ///
+    /// assert_eq!(selection, "hello");
+    /// ```
+    #[must_use]
+    pub const fn select(&self, ranges: Ranges) -> Selection<'a> {
+        Selection::new(self.source, ranges)
+    }
+
+    /// Returns a [`SelectionBuilder`] for building up ranges of this source string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("hello world");
+    /// let mut builder = strloin.builder();
+    /// builder.push(0..5).push(6..11);
+    /// assert_eq!(builder.build(), "helloworld");
+    /// ```
+    #[must_use]
+    pub const fn builder(&self) -> SelectionBuilder<'a> {
+        SelectionBuilder::new(self.source)
+    }
+
+    /// Returns an empty [`RangesIn`] bound to this source string, which checks each range as it
+    /// is pushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::{Error, Strloin};
+    ///
+    /// let strloin = Strloin::new("hello");
+    /// let mut ranges = strloin.ranges_in();
+    /// assert_eq!(ranges.try_push(0..2), Ok(()));
+    /// assert_eq!(ranges.try_push(4..9), Err(Error::OutOfBounds { index: 1, range: 4..9, len: 5 }));
+    /// ```
     #[must_use]
-    pub const fn new(source: &'a str) -> Self {
-        Strloin { source }
+    pub const fn ranges_in(&self) -> RangesIn<'a> {
+        RangesIn::new(self.source)
     }
 
-    /// Extracts a string from the given ranges; if the ranges form a single contiguous region,
-    /// then the result will borrow from the source string. Otherwise, the ranges will be collected
-    /// into an owned string.
+    /// Returns a [`Cursor`] at the start of this source string, for scanning it while recording
+    /// the ranges taken.
     ///
     /// # Examples
     ///
     /// ```
     /// use strloin::Strloin;
     ///
-    /// let strloin = Strloin::new("hello world");
-    ///
-    /// assert_eq!(strloin.from_ranges(&[0..5]), "hello"); // borrowed
-    /// assert_eq!(strloin.from_ranges(&[0..5, 5..11]), "hello world"); // borrowed
-    /// assert_eq!(strloin.from_ranges(&[0..5, 6..11]), "helloworld"); // owned
+    /// let strloin = Strloin::new("1,234,567");
+    /// let mut cursor = strloin.cursor();
+    /// while !cursor.is_done() {
+    ///     cursor.take_while(|c| c.is_ascii_digit());
+    ///     cursor.skip(1);
+    /// }
+    /// assert_eq!(cursor.emit(), "1234567");
     /// ```
     #[must_use]
-    pub fn from_ranges(&self, ranges: &[Range<usize>]) -> Cow<'a, str> {
-        if let Some(range) = collapse_ranges(ranges) {
-            return Borrowed(&self.source[range]);
-        }
+    pub const fn cursor(&self) -> Cursor<'a> {
+        Cursor::new(self.source)
+    }
 
-        Owned(
-            ranges
-                .iter()
-                .map(|r| &self.source[r.clone()])
-                .collect::<String>(),
-        )
+    /// Returns a [`Template`] for interleaving ranges of this source string with literal strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("key=value");
+    /// let got = strloin.template().range(4..9).literal(" for ").range(0..3).build();
+    /// assert_eq!(got, "value for key");
+    /// ```
+    #[must_use]
+    pub const fn template<'t>(&self) -> Template<'a, 't> {
+        Template::new(self.source)
     }
 
-    /// Extracts a string from the given [`Ranges`] object; if the ranges form a single contiguous
-    /// region, then the result will borrow from the source string. Otherwise, the ranges will be
-    /// collected into an owned string. If you're incrementally building up the list of ranges and
-    /// checking each time, using `from_ranges_obj` is more efficient.
+    /// Treats this source string as a slice of a larger document starting at byte `offset`, so
+    /// that ranges can be given in the document's coordinates rather than the slice's.
     ///
     /// # Examples
     ///
     /// ```
-    /// use strloin::{Strloin, Ranges};
+    /// use strloin::Strloin;
     ///
-    /// let strloin = Strloin::new("hello world");
+    /// let document = "# title\nbody text";
+    /// let body = Strloin::new(&document[8..]).with_offset(8);
     ///
-    /// let mut ranges = Ranges::from(0..5);
+    /// let found = document.find("text").unwrap();
+    /// assert_eq!(body.from_ranges(&[found..found + 4]), "text");
+    /// ```
+    #[must_use]
+    pub const fn with_offset(&self, offset: usize) -> OffsetStrloin<'a> {
+        OffsetStrloin {
+            source: self.source,
+            offset,
+        }
+    }
+
+    /// Pairs this source string with a [`StringPool`], whose buffers are reused for owned results
+    /// rather than allocating new strings.
     ///
-    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello"); // borrowed
+    /// # Examples
     ///
-    /// ranges.push(5..11);
-    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello world"); // borrowed
+    /// ```
+    /// use strloin::{StringPool, Strloin};
     ///
-    /// ranges.push(5..11);
-    /// assert_eq!(strloin.from_ranges_obj(&ranges), "hello world world"); // owned
+    /// let pool = StringPool::new();
+    /// let strloin = Strloin::new("hello world");
+    ///
+    /// assert_eq!(strloin.with_pool(&pool).from_ranges(&[0..5, 6..11]), "helloworld");
     /// ```
     #[must_use]
-    pub fn from_ranges_obj(&self, ranges: &Ranges) -> Cow<'a, str> {
-        match ranges.ranges.as_slice() {
-            &[] => Borrowed(""),
-            [range] => Borrowed(&self.source[range.clone()]),
-            ranges => Owned(
-                ranges
-                    .iter()
-                    .map(|r| &self.source[r.clone()])
-                    .collect::<String>(),
-            ),
+    pub const fn with_pool<'p>(&self, pool: &'p StringPool) -> PooledStrloin<'a, 'p> {
+        PooledStrloin {
+            source: self.source,
+            pool,
+        }
+    }
+
+    /// Concatenates `head` and the source slices for `ranges` into an owned string, allocated once
+    /// at its final size.
+    fn concat<T>(&self, head: &str, ranges: T) -> String
+    where
+        T: Iterator<Item = Range<usize>> + Clone,
+    {
+        self.concat_into(String::new(), head, ranges)
+    }
+
+    /// Replaces the contents of `owned` with the concatenation of `head` and the source slices
+    /// for `ranges`, reserving the final size up front, and returns it.
+    #[cfg(not(feature = "fast-owned"))]
+    pub(crate) fn concat_into<T>(&self, mut owned: String, head: &str, ranges: T) -> String
+    where
+        T: Iterator<Item = Range<usize>> + Clone,
+    {
+        let (len, count) = Self::measure(head, ranges.clone());
+        if Self::is_tiny(len, count) {
+            return Self::filled_string(self.gather_tiny(owned.into_bytes(), head, ranges, len));
+        }
+
+        owned.clear();
+        owned.reserve(len);
+        owned.push_str(head);
+        self.for_each_run(ranges, |_, segment| owned.push_str(segment));
+        owned
+    }
+
+    /// Replaces the contents of `owned` with the concatenation of `head` and the source slices
+    /// for `ranges`, reserving the final size up front, by copying raw bytes and skipping UTF-8
+    /// validation of the result.
+    #[cfg(feature = "fast-owned")]
+    pub(crate) fn concat_into<T>(&self, owned: String, head: &str, ranges: T) -> String
+    where
+        T: Iterator<Item = Range<usize>> + Clone,
+    {
+        let (len, count) = Self::measure(head, ranges.clone());
+        if Self::is_tiny(len, count) {
+            return Self::filled_string(self.gather_tiny(owned.into_bytes(), head, ranges, len));
+        }
+
+        let mut bytes = owned.into_bytes();
+        bytes.clear();
+        bytes.reserve(len);
+        bytes.extend_from_slice(head.as_bytes());
+        self.for_each_run(ranges, |_, segment| {
+            bytes.extend_from_slice(segment.as_bytes());
+        });
+
+        // SAFETY: `head` is a `&str`, and each segment is sliced from the `&str` source, which
+        // panics unless the range falls on char boundaries, so every segment is itself valid
+        // UTF-8. A concatenation of valid UTF-8 strings is valid UTF-8.
+        unsafe { String::from_utf8_unchecked(bytes) }
+    }
+
+    /// Returns the bytes copied into a buffer by [`Strloin::from_ranges_to_buf`] as a `&str`.
+    /// Every segment copied was a `&str`, so validation can't fail.
+    #[cfg(not(feature = "fast-owned"))]
+    fn filled_str(filled: &[u8]) -> &str {
+        std::str::from_utf8(filled).expect("concatenated segments of a str are valid UTF-8")
+    }
+
+    /// Returns the bytes copied into a buffer by [`Strloin::from_ranges_to_buf`] as a `&str`,
+    /// skipping UTF-8 validation.
+    #[cfg(feature = "fast-owned")]
+    const fn filled_str(filled: &[u8]) -> &str {
+        // SAFETY: every segment copied into `filled` was sliced from the `&str` source, which
+        // panics unless the range falls on char boundaries, so the concatenation is valid UTF-8.
+        unsafe { std::str::from_utf8_unchecked(filled) }
+    }
+
+    /// Returns the length of the concatenation of `head` and the source slices for `ranges`,
+    /// and the number of ranges.
+    fn measure<T: Iterator<Item = Range<usize>>>(head: &str, ranges: T) -> (usize, usize) {
+        ranges.fold((head.len(), 0), |(len, count), range| {
+            (len + range.len(), count + 1)
+        })
+    }
+
+    /// Returns `true` if `count` ranges totalling `len` bytes are many enough and short enough
+    /// on average for [`Strloin::gather_tiny`] to beat copying them slice by slice.
+    const fn is_tiny(len: usize, count: usize) -> bool {
+        count >= 16 && len <= count * WORD
+    }
+
+    /// Concatenates `head` and the source slices for `ranges`, which total `len` bytes, into
+    /// `bytes`, replacing its contents. Each run of up to [`WORD`] bytes is copied as a whole
+    /// word when the source has that many bytes left: a fixed-size copy is far cheaper than a
+    /// call to `memcpy`, and the bytes it copies past the end of the run are overwritten by the
+    /// next one or truncated at the end.
+    fn gather_tiny<T>(&self, mut bytes: Vec<u8>, head: &str, ranges: T, len: usize) -> Vec<u8>
+    where
+        T: Iterator<Item = Range<usize>>,
+    {
+        let source = self.source.as_bytes();
+        bytes.clear();
+        bytes.resize(len + WORD, 0);
+        bytes[..head.len()].copy_from_slice(head.as_bytes());
+
+        let mut filled = head.len();
+        self.for_each_run(ranges, |start, segment| {
+            if segment.len() <= WORD && start + WORD <= source.len() {
+                bytes[filled..filled + WORD].copy_from_slice(&source[start..start + WORD]);
+            } else {
+                bytes[filled..filled + segment.len()].copy_from_slice(segment.as_bytes());
+            }
+            filled += segment.len();
+        });
+
+        bytes.truncate(len);
+        bytes
+    }
+
+    /// Returns the bytes copied by [`Strloin::gather_tiny`] or [`Strloin::par_concat`] as a
+    /// `String`. Every segment copied was a `&str`, so validation can't fail.
+    #[cfg(not(feature = "fast-owned"))]
+    fn filled_string(filled: Vec<u8>) -> String {
+        String::from_utf8(filled).expect("concatenated segments of a str are valid UTF-8")
+    }
+
+    /// Returns the bytes copied by [`Strloin::gather_tiny`] or [`Strloin::par_concat`] as a
+    /// `String`, skipping UTF-8 validation.
+    #[cfg(feature = "fast-owned")]
+    fn filled_string(filled: Vec<u8>) -> String {
+        // SAFETY: every segment copied into `filled` was sliced from the `&str` source, which
+        // panics unless the range falls on char boundaries, so the concatenation is valid UTF-8.
+        unsafe { String::from_utf8_unchecked(filled) }
+    }
+
+    /// Collapses `ranges` into a single contiguous range, if possible, as with
+    /// [`collapse_ranges_iter`], checking the empty ranges it skips as well.
+    pub(crate) fn collapse<T>(&self, ranges: T) -> Option<Range<usize>>
+    where
+        T: IntoIterator<Item = Range<usize>>,
+    {
+        collapse_ranges_iter(ranges.into_iter().inspect(|range| self.check_empty(range)))
+    }
+
+    /// Panics, just as slicing it would, if `range` is empty but out of bounds or inside a
+    /// character. Empty ranges are skipped rather than sliced when collapsing, and this keeps
+    /// them from hiding a bad range that [`Strloin::check_ranges`] would report.
+    fn check_empty(&self, range: &Range<usize>) {
+        if range.start == range.end {
+            let _ = &self.source[range.clone()];
+        }
+    }
+
+    /// Calls `f` with the start and source slice of each run of ranges that continue one
+    /// another, so that a run of many tiny ranges is sliced and copied once rather than range by
+    /// range.
+    fn for_each_run<T, F>(&self, ranges: T, mut f: F)
+    where
+        T: Iterator<Item = Range<usize>>,
+        F: FnMut(usize, &'a str),
+    {
+        let mut run = 0..0;
+        for range in ranges {
+            self.check_empty(&range);
+            if !merge_into(&mut run, &range) {
+                let run = std::mem::replace(&mut run, range);
+                f(run.start, &self.source[run]);
+            }
         }
+        f(run.start, &self.source[run]);
     }
 }
 
@@ -87,7 +1147,78 @@ impl<'a> From<&'a str> for Strloin<'a> {
     }
 }
 
+/// An iterator over the range of each field of a source string, created by
+/// [`Strloin::split_ranges`] and [`Strloin::splitn_ranges`].
+#[derive(Debug, Clone)]
+pub struct SplitRanges<'a, 'p> {
+    matches: MatchIndices<'a, &'p str>,
+    start: usize,
+    end: usize,
+    remaining: usize,
+}
+
+impl Iterator for SplitRanges<'_, '_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.remaining {
+            0 => return None,
+            1 => {}
+            _ => {
+                if let Some((i, m)) = self.matches.next() {
+                    self.remaining -= 1;
+                    let field = self.start..i;
+                    self.start = i + m.len();
+                    return Some(field);
+                }
+            }
+        }
+
+        self.remaining = 0;
+        Some(self.start..self.end)
+    }
+}
+
+impl FusedIterator for SplitRanges<'_, '_> {}
+
+/// An iterator over the range of each line of a source string, created by
+/// [`Strloin::lines_ranges`].
+#[derive(Debug, Clone)]
+pub struct LinesRanges<'a> {
+    source: &'a str,
+    start: usize,
+}
+
+impl Iterator for LinesRanges<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self
+            .source
+            .get(self.start..)
+            .filter(|rest| !rest.is_empty())?;
+        let start = self.start;
+        let mut end = rest.find('\n').map_or(self.source.len(), |i| start + i + 1);
+        self.start = end;
+
+        let line = &self.source.as_bytes()[start..end];
+        if line.ends_with(b"\n") {
+            end -= 1;
+            if line.ends_with(b"\r\n") {
+                end -= 1;
+            }
+        }
+
+        Some(start..end)
+    }
+}
+
+impl FusedIterator for LinesRanges<'_> {}
+
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+// The original tests borrow their `&str` sources and don't name their panics; keep them as written.
+#[allow(clippy::needless_borrow, clippy::should_panic_without_expect)]
 mod tests {
     use super::*;
 
@@ -102,6 +1233,16 @@ mod tests {
                 let got_from_slice = strloin.from_ranges(input);
                 assert_eq!(got_from_slice, expected, "from_ranges");
 
+                let got_with_policy = strloin
+                    .from_ranges_with(input, &CollapsePolicy::new())
+                    .unwrap();
+                assert_eq!(got_with_policy, expected, "from_ranges_with");
+                assert_eq!(
+                    matches!(got_with_policy, Borrowed(_)),
+                    matches!(got_from_slice, Borrowed(_)),
+                    "from_ranges_with borrows like from_ranges"
+                );
+
                 let mut ranges = Ranges::new();
                 for range in input {
                     ranges.push(range.clone());
@@ -109,6 +1250,18 @@ mod tests {
                 let got_from_obj = strloin.from_ranges_obj(&ranges);
                 assert_eq!(got_from_obj, expected, "from_ranges_obj");
 
+                let mut packed = PackedRanges::new();
+                for range in input {
+                    packed.push(range.clone());
+                }
+                let got_from_packed = strloin.from_packed_ranges(&packed);
+                assert_eq!(got_from_packed, expected, "from_packed_ranges");
+                assert_eq!(
+                    matches!(got_from_packed, Borrowed(_)),
+                    matches!(got_from_obj, Borrowed(_)),
+                    "from_packed_ranges borrows like from_ranges_obj"
+                );
+
                 if $is_borrow {
                     assert!(
                         matches!(got_from_slice, Borrowed(_)),
@@ -141,6 +1294,497 @@ mod tests {
         from_ranges_ok!(strloin, &[0..5, 6..11], "helloworld", false);
         from_ranges_ok!(strloin, &[6..11, 5..6, 0..5], "world hello", false);
         from_ranges_ok!(strloin, &[0..6, 0..5], "hello hello", false);
+        from_ranges_ok!(strloin, &[0..5, 7..7, 5..11], "hello world", true);
+        from_ranges_ok!(strloin, &[3..3, 0..5, 11..11], "hello", true);
+        from_ranges_ok!(strloin, &[0..1, 2..3, 3..4, 4..5, 0..1], "hlloh", false);
+    }
+
+    #[test]
+    fn from_ranges_tiny() {
+        let source = "ab".repeat(500) + "ü";
+        let strloin = Strloin::new(&source);
+
+        let ranges: Vec<_> = (0..1000).step_by(2).map(|i| i..i + 1).collect();
+        assert_eq!(strloin.from_ranges(&ranges), "a".repeat(500));
+
+        let ranges: Vec<_> = (0..1002).step_by(2).rev().map(|i| i..i + 2).collect();
+        assert_eq!(
+            strloin.from_ranges(&ranges),
+            "ü".to_owned() + &"ab".repeat(500)
+        );
+    }
+
+    #[test]
+    fn from_ranges_with() {
+        macro_rules! from_ranges_with_ok {
+            ($policy:expr, $input:expr, $expected:expr, $is_borrow:expr) => {
+                let strloin = Strloin::new("hello world");
+                let got = strloin.from_ranges_with(&$input, &$policy).unwrap();
+                assert_eq!(got, $expected, "from_ranges_with");
+                assert_eq!(matches!(got, Borrowed(_)), $is_borrow, "borrow");
+            };
+        }
+
+        let lenient = CollapsePolicy {
+            merge_overlapping: true,
+            ..CollapsePolicy::new()
+        };
+        let keep_empty = CollapsePolicy {
+            ignore_empty: false,
+            ..CollapsePolicy::new()
+        };
+
+        from_ranges_with_ok!(lenient, [0..5, 3..11], "hello world", true);
+        from_ranges_with_ok!(lenient, [0..5, 3..8, 1..4], "hello woell", false);
+        from_ranges_with_ok!(lenient, [6..11, 0..3, 2..5], "worldhello", false);
+        from_ranges_with_ok!(keep_empty, [0..5, 7..7, 5..11], "hello world", false);
+        from_ranges_with_ok!(keep_empty, [0..5, 5..5, 5..11], "hello world", true);
+
+        let strloin = Strloin::new("hello world");
+        assert_eq!(
+            strloin
+                .from_ranges_with(&[0..5, 6..11, 11..4], &lenient)
+                .unwrap_err(),
+            Error::Inverted {
+                index: 2,
+                range: 11..4
+            }
+        );
+        assert_eq!(
+            strloin
+                .from_ranges_with(&[0..5, 6..12], &lenient)
+                .unwrap_err(),
+            Error::OutOfBounds {
+                index: 1,
+                range: 6..12,
+                len: 11
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "range 6..12 at index 1 is out of bounds of a string of length 11")]
+    fn from_ranges_with_panic() {
+        let policy = CollapsePolicy {
+            panic_on_invalid: true,
+            ..CollapsePolicy::new()
+        };
+        let _ = Strloin::new("hello world").from_ranges_with(&[0..5, 6..12], &policy);
+    }
+
+    #[test]
+    fn from_named_ranges() {
+        let strloin = Strloin::new("hello world");
+        let mut named = NamedRanges::new();
+        named.push(1, 0..5);
+        named.push(2, 6..11);
+        named.push(1, 5..6);
+        named.push(3, 0..1);
+        named.push(3, 10..11);
+
+        let got = strloin.from_named_ranges(&named);
+        assert_eq!(got.len(), 3);
+        assert!(matches!(got[&1], Borrowed("hello ")));
+        assert!(matches!(got[&2], Borrowed("world")));
+        assert!(matches!(&got[&3], Owned(s) if s == "hd"));
+
+        let empty: NamedRanges<u8> = NamedRanges::new();
+        assert!(strloin.from_named_ranges(&empty).is_empty());
+    }
+
+    #[test]
+    fn split_ranges() {
+        macro_rules! split_ranges_ok {
+            ($source:expr, $delimiter:expr) => {
+                let source: &str = $source;
+                let strloin = Strloin::new(source);
+
+                let got: Vec<_> = strloin
+                    .split_ranges($delimiter)
+                    .map(|r| &source[r])
+                    .collect();
+                let expected: Vec<_> = source.split($delimiter).collect();
+                assert_eq!(got, expected, "split_ranges");
+
+                for n in 0..5 {
+                    let got: Vec<_> = strloin
+                        .splitn_ranges(n, $delimiter)
+                        .map(|r| &source[r])
+                        .collect();
+                    let expected: Vec<_> = source.splitn(n, $delimiter).collect();
+                    assert_eq!(got, expected, "splitn_ranges {}", n);
+                }
+            };
+        }
+
+        split_ranges_ok!("", ",");
+        split_ranges_ok!("a", ",");
+        split_ranges_ok!("a,b,c", ",");
+        split_ranges_ok!(",a,,b,", ",");
+        split_ranges_ok!("a--b---c", "--");
+        split_ranges_ok!("ab", "");
+        split_ranges_ok!("héllo wörld", "ö");
+
+        let mut fields = Strloin::new("a,b").split_ranges(",");
+        assert_eq!(fields.by_ref().count(), 2);
+        assert_eq!(fields.next(), None);
+    }
+
+    #[test]
+    fn lines_ranges() {
+        for source in [
+            "",
+            "\n",
+            "\r\n",
+            "one",
+            "one\n",
+            "one\ntwo",
+            "one\r\ntwo\r\n",
+            "\n\none\n\n",
+            "one\rtwo\r",
+            "héllo\r\nwörld",
+        ] {
+            let got: Vec<_> = Strloin::new(source)
+                .lines_ranges()
+                .map(|r| &source[r])
+                .collect();
+            let expected: Vec<_> = source.lines().collect();
+            assert_eq!(got, expected, "{source:?}");
+        }
+
+        let mut lines = Strloin::new("a\nb").lines_ranges();
+        assert_eq!(lines.by_ref().count(), 2);
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn from_ranges_to_buf() {
+        macro_rules! from_ranges_to_buf_ok {
+            ($input:expr) => {
+                let strloin = Strloin::new("héllo wörld");
+                let input: &[Range<usize>] = &$input;
+                let expected = strloin.from_ranges(input);
+
+                let mut buf = [0xff; 32];
+                assert_eq!(strloin.from_ranges_to_buf(input, &mut buf), Ok(&*expected));
+
+                let mut buf = vec![0; expected.len()];
+                assert_eq!(strloin.from_ranges_to_buf(input, &mut buf), Ok(&*expected));
+
+                if !expected.is_empty() {
+                    let mut buf = vec![0; expected.len() - 1];
+                    assert_eq!(
+                        strloin.from_ranges_to_buf(input, &mut buf),
+                        Err(Error::BufferTooSmall {
+                            needed: expected.len(),
+                            len: expected.len() - 1
+                        })
+                    );
+                    assert!(buf.iter().all(|&b| b == 0));
+                }
+            };
+        }
+
+        from_ranges_to_buf_ok!([]);
+        from_ranges_to_buf_ok!([0..0]);
+        from_ranges_to_buf_ok!([0..6]);
+        from_ranges_to_buf_ok!([0..3, 3..6, 7..13]);
+        from_ranges_to_buf_ok!([7..13, 6..7, 0..6, 0..1]);
+    }
+
+    #[test]
+    fn gather_tiny() {
+        macro_rules! gather_tiny_ok {
+            ($source:expr, $input:expr) => {
+                let source: &str = $source;
+                let input: &[Range<usize>] = &$input;
+                let expected: String = input.iter().map(|r| &source[r.clone()]).collect();
+                let (len, count) = Strloin::measure("", input.iter().cloned());
+                assert!(Strloin::is_tiny(len, count), "tiny {:?}", input);
+
+                let strloin = Strloin::new(source);
+                assert_eq!(strloin.from_ranges(input), expected, "{:?}", input);
+
+                let reused = String::from("leftover bytes from an earlier use");
+                let got = strloin.concat_into(reused, "head:", input.iter().cloned());
+                assert_eq!(got, format!("head:{}", expected), "concat_into {:?}", input);
+            };
+        }
+
+        let source = "abcdefghijklmnopqrstuvwxyz".repeat(2);
+        let source = source.as_str();
+        gather_tiny_ok!(
+            source,
+            (0..52).step_by(2).map(|i| i..i + 1).collect::<Vec<_>>()
+        );
+        gather_tiny_ok!(source, (0..24).map(|i| i..i + 1).rev().collect::<Vec<_>>());
+        gather_tiny_ok!(source, (0..22).map(|i| i..i + i % 5).collect::<Vec<_>>());
+        gather_tiny_ok!(source, [vec![0..9, 10..26], vec![25..26; 16]].concat());
+
+        let source = "héllo wörld, ça va? ✓ ñ".repeat(3);
+        let ranges: Vec<_> = source
+            .char_indices()
+            .step_by(2)
+            .map(|(i, c)| i..i + c.len_utf8())
+            .collect();
+        gather_tiny_ok!(&source, ranges);
+
+        let source = "héllo wörld, ça va? ✓ ñ";
+        gather_tiny_ok!(
+            source,
+            [0..3, 4..10, 21..26, 26..29, 27..29]
+                .iter()
+                .cloned()
+                .cycle()
+                .take(20)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn par_from_ranges() {
+        macro_rules! par_from_ranges_ok {
+            ($input:expr) => {
+                let strloin = Strloin::new("héllo wörld");
+                let input: &[Range<usize>] = &$input;
+                let expected = strloin.from_ranges(input);
+
+                let got = strloin.par_from_ranges(input);
+                assert_eq!(got, expected, "par_from_ranges {:?}", input);
+                assert_eq!(
+                    matches!(got, Borrowed(_)),
+                    matches!(expected, Borrowed(_)),
+                    "par_from_ranges borrows like from_ranges"
+                );
+
+                let len = input.iter().map(ExactSizeIterator::len).sum();
+                for threads in 1..=4 {
+                    let got = strloin.par_concat(input, len, threads);
+                    assert_eq!(got, expected, "par_concat {:?} on {}", input, threads);
+                }
+            };
+        }
+
+        par_from_ranges_ok!([]);
+        par_from_ranges_ok!([0..6]);
+        par_from_ranges_ok!([0..3, 4..6]);
+        par_from_ranges_ok!([0..3, 3..6, 7..13]);
+        par_from_ranges_ok!([7..13, 6..7, 0..0, 0..6, 0..1]);
+
+        let source = "abcdefghij".repeat(1 << 20);
+        let strloin = Strloin::new(&source);
+        let ranges: Vec<_> = (0..source.len())
+            .step_by(10)
+            .map(|i| i + 1..i + 9)
+            .collect();
+        assert_eq!(
+            strloin.par_from_ranges(&ranges),
+            strloin.from_ranges(&ranges)
+        );
+    }
+
+    #[test]
+    fn from_index_ranges() {
+        let narrow = |index| u8::from_usize(index).expect("fits in u8");
+        macro_rules! from_index_ranges_ok {
+            ($input:expr) => {
+                let strloin = Strloin::new("hello world");
+                let input: &[Range<usize>] = &$input;
+                let expected = strloin.from_ranges(input);
+
+                let narrow: Vec<Range<u8>> = input
+                    .iter()
+                    .map(|r| narrow(r.start)..narrow(r.end))
+                    .collect();
+                let got = strloin.from_index_ranges(&narrow);
+                assert_eq!(got, expected);
+                assert_eq!(matches!(got, Borrowed(_)), matches!(expected, Borrowed(_)));
+                assert_eq!(strloin.from_index_ranges(input), expected);
+            };
+        }
+
+        from_index_ranges_ok!([]);
+        from_index_ranges_ok!([3..3]);
+        from_index_ranges_ok!([0..5, 5..11]);
+        from_index_ranges_ok!([0..5, 6..11]);
+        from_index_ranges_ok!([6..11, 5..6, 0..5]);
+        from_index_ranges_ok!([0..0, 4..4, 6..11]);
+    }
+
+    #[test]
+    fn try_from_ranges() {
+        macro_rules! try_from_ranges_ok {
+            ($input:expr, $expected:expr) => {
+                let strloin = Strloin::new("héllo wörld");
+                let input: &[Range<usize>] = &$input;
+                let got = strloin.try_from_ranges(input);
+                let expected: Result<&str, Error> = $expected;
+                assert_eq!(got.as_deref().map_err(Clone::clone), expected);
+                assert_eq!(strloin.check_ranges(input), expected.map(|_| ()));
+            };
+        }
+
+        try_from_ranges_ok!([], Ok(""));
+        try_from_ranges_ok!([0..6, 7..13], Ok("héllowörld"));
+        try_from_ranges_ok!([0..13], Ok("héllo wörld"));
+        try_from_ranges_ok!([13..13], Ok(""));
+        try_from_ranges_ok!(
+            [0..6, 9..7],
+            Err(Error::Inverted {
+                index: 1,
+                range: 9..7
+            })
+        );
+        try_from_ranges_ok!(
+            [0..14],
+            Err(Error::OutOfBounds {
+                index: 0,
+                range: 0..14,
+                len: 13
+            })
+        );
+        try_from_ranges_ok!(
+            [14..14],
+            Err(Error::OutOfBounds {
+                index: 0,
+                range: 14..14,
+                len: 13
+            })
+        );
+        try_from_ranges_ok!(
+            [0..6, 2..4],
+            Err(Error::NotCharBoundary { index: 1, pos: 2 })
+        );
+        try_from_ranges_ok!(
+            [0..6, 7..9],
+            Err(Error::NotCharBoundary { index: 1, pos: 9 })
+        );
+    }
+
+    #[test]
+    fn contiguous_prefix() {
+        macro_rules! contiguous_prefix_ok {
+            ($input:expr, $expected_head:expr, $expected_index:expr) => {
+                let strloin = Strloin::new("hello world");
+                let input: &[Range<usize>] = &$input;
+                let (head, index) = strloin.contiguous_prefix(input);
+                assert_eq!((head, index), ($expected_head, $expected_index));
+
+                let tail = strloin.from_ranges(&input[index..]);
+                assert_eq!(
+                    head.to_owned() + &tail,
+                    strloin.from_ranges(input),
+                    "head and tail"
+                );
+                if index == input.len() {
+                    assert!(matches!(strloin.from_ranges(input), Borrowed(_)));
+                }
+            };
+        }
+
+        contiguous_prefix_ok!([], "", 0);
+        contiguous_prefix_ok!([0..5], "hello", 1);
+        contiguous_prefix_ok!([0..5, 5..11], "hello world", 2);
+        contiguous_prefix_ok!([0..5, 6..11], "hello", 1);
+        contiguous_prefix_ok!([0..5, 7..7, 5..6, 0..5], "hello ", 3);
+        contiguous_prefix_ok!([3..3, 6..11, 0..1], "world", 2);
+
+        let strloin = Strloin::new("hello world");
+        assert_eq!(
+            strloin.contiguous_prefix(&[0..5, 5..4, 5..11]),
+            ("hello", 1)
+        );
+        assert_eq!(strloin.contiguous_prefix(&[5..4, 0..5]), ("", 0));
+    }
+
+    #[test]
+    fn from_ranges_rev() {
+        macro_rules! from_ranges_rev_ok {
+            ($input:expr, $expected:expr, $is_borrow:expr) => {
+                let strloin = Strloin::new("hello world");
+                let input: &[Range<usize>] = &$input;
+                let got = strloin.from_ranges_rev(input);
+                assert_eq!(got, $expected, "from_ranges_rev");
+
+                let reversed: Vec<_> = input.iter().rev().cloned().collect();
+                assert_eq!(
+                    got,
+                    strloin.from_ranges(&reversed),
+                    "same as reversing first"
+                );
+                assert_eq!(matches!(got, Borrowed(_)), $is_borrow, "borrow");
+            };
+        }
+
+        from_ranges_rev_ok!([], "", true);
+        from_ranges_rev_ok!([0..5], "hello", true);
+        from_ranges_rev_ok!([5..11, 0..5], "hello world", true);
+        from_ranges_rev_ok!([0..5, 5..11], " worldhello", false);
+        from_ranges_rev_ok!([6..11, 5..6, 0..5], "hello world", true);
+        from_ranges_rev_ok!([0..5, 6..11], "worldhello", false);
+    }
+
+    #[test]
+    fn map_ranges() {
+        macro_rules! map_ranges_ok {
+            ($input:expr, $expected:expr, $is_borrow:expr) => {
+                let strloin = Strloin::new("hello world");
+                let got = strloin.map_ranges($input, |s| {
+                    if s.contains('o') {
+                        Owned(s.replace('o', "0"))
+                    } else {
+                        Borrowed(s)
+                    }
+                });
+                assert_eq!(got, $expected, "map_ranges");
+
+                if $is_borrow {
+                    assert!(matches!(got, Borrowed(_)), "expected borrow");
+                } else {
+                    assert!(matches!(got, Owned(_)), "expected owned");
+                }
+            };
+        }
+
+        map_ranges_ok!(&[], "", true);
+        map_ranges_ok!(&[0..3], "hel", true);
+        map_ranges_ok!(&[0..5], "hell0", false);
+        map_ranges_ok!(&[0..2, 2..4], "hell", true);
+        map_ranges_ok!(&[0..2, 2..4, 4..6], "hell0 ", false);
+        map_ranges_ok!(&[0..4, 4..6, 6..7], "hell0 w", false);
+        map_ranges_ok!(&[0..2, 3..4], "hel", false);
+        map_ranges_ok!(&[0..5, 6..11], "hell0w0rld", false);
+        map_ranges_ok!(&[6..7, 0..3], "whel", false);
+    }
+
+    #[test]
+    fn map_ranges_subslice() {
+        let strloin = Strloin::new(" hello world ");
+        let got = strloin.map_ranges(&[0..7], |s| Borrowed(s.trim()));
+        assert!(matches!(got, Borrowed("hello")));
+
+        let got = strloin.map_ranges(&[0..6, 6..13], |s| Borrowed(s.trim()));
+        assert!(matches!(got, Owned(_)));
+        assert_eq!(got, "helloworld");
+    }
+
+    #[test]
+    fn map_ranges_empty_mapped() {
+        let strloin = Strloin::new("hello world");
+        let mark_empty = |s: &'static str| {
+            if s.is_empty() {
+                Owned(String::from("X"))
+            } else {
+                Borrowed(s)
+            }
+        };
+
+        let got = strloin.map_ranges(&[0..5, 7..7, 5..11], mark_empty);
+        assert_eq!(got, "helloX world");
+
+        let got = strloin.map_ranges(&[2..5, 0..0, 5..8], mark_empty);
+        assert_eq!(got, "lloX wo");
     }
 
     #[test]
@@ -158,4 +1802,47 @@ mod tests {
         let strloin = Strloin::new(&string);
         let _ = strloin.from_ranges(&[2..1, 1..4]);
     }
+
+    #[test]
+    #[should_panic(expected = "when slicing")]
+    fn invalid_range_message() {
+        let strloin = Strloin::new("hello world");
+        let _ = strloin.from_ranges(&[1..0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "when slicing")]
+    fn invalid_ranges_message() {
+        let strloin = Strloin::new("hello world");
+        let _ = strloin.from_ranges(&[2..1, 1..4]);
+    }
+
+    #[test]
+    fn empty_ranges_checked() {
+        let strloin = Strloin::new("héllo world");
+        for input in [&[0..3, 100..100][..], &[100..100], &[0..1, 5..6, 2..2]] {
+            assert!(strloin.check_ranges(input).is_err(), "{input:?}");
+            assert!(strloin.try_from_ranges(input).is_err(), "{input:?}");
+
+            let panicked = std::panic::catch_unwind(|| strloin.from_ranges(input)).is_err();
+            assert!(panicked, "from_ranges {input:?}");
+            let panicked = std::panic::catch_unwind(|| strloin.from_ranges_rev(input)).is_err();
+            assert!(panicked, "from_ranges_rev {input:?}");
+            let panicked = std::panic::catch_unwind(|| strloin.from_index_ranges(input)).is_err();
+            assert!(panicked, "from_index_ranges {input:?}");
+        }
+
+        assert_eq!(strloin.try_from_ranges(&[0..3, 12..12]).unwrap(), "hé");
+        assert!(matches!(
+            strloin.from_ranges(&[0..3, 12..12]),
+            Borrowed("hé")
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn empty_range_out_of_bounds() {
+        let strloin = Strloin::new("hello world");
+        let _ = strloin.from_ranges(&[0..5, 100..100]);
+    }
 }
@@ -0,0 +1,158 @@
+use crate::cow::{Borrowed, Cow, Owned};
+use crate::ranges::merge_into;
+use std::ops::Range;
+
+/// A builder that interleaves ranges of a source string with literal strings, for light
+/// rewriting.
+///
+/// Building it borrows from the source string if the template is a single contiguous region
+/// with no literals; otherwise, the pieces are concatenated into an owned string.
+///
+/// Construct one with [`Strloin::template`](crate::Strloin::template).
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Borrowed, Strloin};
+///
+/// let strloin = Strloin::new("hello world");
+///
+/// let got = strloin.template().range(0..5).literal(": ").range(6..11).build();
+/// assert_eq!(got, "hello: world");
+///
+/// let got = strloin.template().range(0..5).range(5..11).literal("").build();
+/// assert!(matches!(got, Borrowed("hello world")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template<'a, 't> {
+    source: &'a str,
+    parts: Vec<Part<'t>>,
+}
+
+/// A piece of a [`Template`].
+#[derive(Debug, Clone)]
+enum Part<'t> {
+    Range(Range<usize>),
+    Literal(&'t str),
+}
+
+impl<'a, 't> Template<'a, 't> {
+    pub(crate) const fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Appends a range of the source string, collapsing it into a preceding range if possible,
+    /// as with [`Ranges::push`](crate::Ranges::push).
+    #[must_use]
+    pub fn range(mut self, range: Range<usize>) -> Self {
+        if let Some(Part::Range(last)) = self.parts.last_mut() {
+            if merge_into(last, &range) {
+                return self;
+            }
+        }
+
+        self.parts.push(Part::Range(range));
+        self
+    }
+
+    /// Appends a literal string. Empty literals add nothing, so they don't prevent borrowing.
+    #[must_use]
+    pub fn literal(mut self, literal: &'t str) -> Self {
+        if !literal.is_empty() {
+            self.parts.push(Part::Literal(literal));
+        }
+        self
+    }
+
+    /// Returns an iterator over the ranges of the source string in the template, in order,
+    /// skipping the literals.
+    pub fn ranges(&self) -> impl Iterator<Item = &Range<usize>> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Range(range) => Some(range),
+            Part::Literal(_) => None,
+        })
+    }
+
+    /// Returns the number of bytes the template will build.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Range(range) => range.len(),
+                Part::Literal(literal) => literal.len(),
+            })
+            .sum()
+    }
+
+    /// Returns `true` if the template will build an empty string.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Builds the template; if it is a single contiguous range with no literals, then the result
+    /// will borrow from the source string. Otherwise, the ranges and literals will be
+    /// concatenated into an owned string, allocated once at its final size.
+    #[must_use]
+    pub fn build(&self) -> Cow<'a, str> {
+        match self.parts.as_slice() {
+            [] => Borrowed(""),
+            [Part::Range(range)] => Borrowed(&self.source[range.clone()]),
+            parts => {
+                let mut owned = String::with_capacity(self.len());
+                for part in parts {
+                    owned.push_str(match part {
+                        Part::Range(range) => &self.source[range.clone()],
+                        Part::Literal(literal) => literal,
+                    });
+                }
+                Owned(owned)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Borrowed, Strloin};
+
+    #[test]
+    fn build() {
+        let strloin = Strloin::new("hello world");
+        let template = || strloin.template();
+
+        assert!(matches!(template().build(), Borrowed("")));
+        assert!(matches!(template().range(0..5).build(), Borrowed("hello")));
+        assert!(matches!(
+            template().range(0..5).range(5..6).range(6..11).build(),
+            Borrowed("hello world")
+        ));
+        assert!(matches!(
+            template().literal("").range(3..3).range(6..11).build(),
+            Borrowed("world")
+        ));
+
+        let got = template().literal("say ").build();
+        assert_eq!((got.as_ref(), matches!(got, Borrowed(_))), ("say ", false));
+
+        let got = template()
+            .range(0..5)
+            .literal(", ")
+            .range(6..11)
+            .literal("!");
+        assert_eq!(got.len(), 13);
+        assert_eq!(got.build(), "hello, world!");
+
+        let got = template().range(6..11).range(5..6).range(0..5).build();
+        assert_eq!(got, "world hello");
+
+        let got = template().range(0..5).literal("").range(5..11).build();
+        assert!(matches!(got, Borrowed("hello world")));
+
+        assert!(template().range(4..4).literal("").is_empty());
+    }
+}
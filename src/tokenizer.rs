@@ -0,0 +1,157 @@
+use crate::ranges::Ranges;
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+/// A span of a source string found by a [`Tokenizer`]: either a token, or a run of one or more
+/// delimiters between tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    /// The byte range of the span in the source string.
+    pub range: Range<usize>,
+
+    /// Whether the span is a run of delimiters rather than a token.
+    pub delimiter: bool,
+}
+
+/// An iterator over the tokens and delimiter runs of a source string, created by
+/// [`Strloin::tokenize`](crate::Strloin::tokenize).
+///
+/// Each [`Token`] is as long as possible, so tokens and delimiter runs alternate and together
+/// cover the whole source string. Use [`Tokenizer::tokens`] to skip the delimiters.
+///
+/// # Examples
+///
+/// ```
+/// use strloin::{Strloin, Token};
+///
+/// let strloin = Strloin::new("a, b");
+/// let spans: Vec<Token> = strloin.tokenize(&[',', ' ']).collect();
+/// assert_eq!(
+///     spans,
+///     [
+///         Token { range: 0..1, delimiter: false },
+///         Token { range: 1..3, delimiter: true },
+///         Token { range: 3..4, delimiter: false },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a, 'd> {
+    source: &'a str,
+    delimiters: &'d [char],
+    start: usize,
+}
+
+impl<'a, 'd> Tokenizer<'a, 'd> {
+    pub(crate) const fn new(source: &'a str, delimiters: &'d [char]) -> Self {
+        Self {
+            source,
+            delimiters,
+            start: 0,
+        }
+    }
+
+    /// Collects the remaining tokens into [`Ranges`], skipping the delimiter runs. Tokens are
+    /// always separated by delimiters, so none of them collapse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strloin::Strloin;
+    ///
+    /// let strloin = Strloin::new("  hello   world ");
+    /// let tokens = strloin.tokenize(&[' ']).tokens();
+    /// assert_eq!(tokens.ranges, [2..7, 10..15]);
+    /// assert_eq!(strloin.from_ranges_obj(&tokens), "helloworld");
+    /// ```
+    #[must_use]
+    pub fn tokens(self) -> Ranges {
+        self.filter(|token| !token.delimiter)
+            .map(|token| token.range)
+            .collect()
+    }
+}
+
+impl Iterator for Tokenizer<'_, '_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.source[self.start..];
+        let delimiter = self.delimiters.contains(&rest.chars().next()?);
+        let len = rest
+            .char_indices()
+            .find(|(_, c)| self.delimiters.contains(c) != delimiter)
+            .map_or(rest.len(), |(i, _)| i);
+
+        let start = self.start;
+        self.start += len;
+        Some(Token {
+            range: start..self.start,
+            delimiter,
+        })
+    }
+}
+
+impl FusedIterator for Tokenizer<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::Strloin;
+
+    #[test]
+    fn tokenize() {
+        macro_rules! tokenize_ok {
+            ($source:expr, $delimiters:expr, $expected:expr) => {
+                let source: &str = $source;
+                let got: Vec<_> = Strloin::new(source)
+                    .tokenize(&$delimiters)
+                    .map(|token| (&source[token.range], token.delimiter))
+                    .collect();
+                let expected: &[(&str, bool)] = &$expected;
+                assert_eq!(got, expected);
+            };
+        }
+
+        tokenize_ok!("", [' '], []);
+        tokenize_ok!("abc", [], [("abc", false)]);
+        tokenize_ok!("   ", [' '], [("   ", true)]);
+        tokenize_ok!("a b", [' '], [("a", false), (" ", true), ("b", false)]);
+        tokenize_ok!(
+            " a,\tb ",
+            [' ', ',', '\t'],
+            [
+                (" ", true),
+                ("a", false),
+                (",\t", true),
+                ("b", false),
+                (" ", true)
+            ]
+        );
+        tokenize_ok!(
+            "héllo→wörld",
+            ['→', 'é'],
+            [
+                ("h", false),
+                ("é", true),
+                ("llo", false),
+                ("→", true),
+                ("wörld", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens() {
+        let strloin = Strloin::new(",a,,bc,");
+        let tokens = strloin.tokenize(&[',']).tokens();
+        assert_eq!(tokens.ranges, [1..2, 4..6]);
+        assert_eq!(strloin.from_ranges_obj(&tokens), "abc");
+
+        let mut tokenizer = strloin.tokenize(&[',']);
+        tokenizer.next();
+        assert_eq!(tokenizer.tokens().ranges, [1..2, 4..6]);
+        let mut tokenizer = strloin.tokenize(&[]);
+        tokenizer.next();
+        assert!(tokenizer.tokens().is_empty());
+    }
+}